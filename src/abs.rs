@@ -0,0 +1,202 @@
+use crate::{
+    budget::Budget,
+    ic3::IC3,
+    options::Options,
+    report::Report,
+    transys::{unroll::TransysUnroll, Transys},
+    witness_encode, Engine, Error,
+};
+use aig::Aig;
+use giputils::hash::GHashSet;
+use logic_form::{Clause, Cube, Lit, Var};
+use satif::Satif;
+
+/// localization abstraction refinement, ic3 checks the abstraction in which the invisible
+/// latchs are free inputs, abstract counterexamples are checked by bmc in which each
+/// invisible latch is enabled by an activation literal, and the invisible latchs in the
+/// unsat core of a spurious counterexample become visible
+pub struct Localization {
+    options: Options,
+    ts: Transys,
+    visible: GHashSet<Var>,
+    ic3: Option<IC3>,
+    /// bmc solver and unrolling of a real counterexample with its depth
+    cex: Option<(Box<dyn Satif>, TransysUnroll, usize)>,
+    num_refine: usize,
+}
+
+impl Localization {
+    pub fn new(options: Options, ts: Transys) -> Self {
+        let mut visible: GHashSet<Var> = ts.dependence[ts.bad.var()]
+            .iter()
+            .copied()
+            .filter(|v| ts.is_latch(*v))
+            .collect();
+        if ts.is_latch(ts.bad.var()) {
+            visible.insert(ts.bad.var());
+        }
+        Self {
+            options,
+            ts,
+            visible,
+            ic3: None,
+            cex: None,
+            num_refine: 0,
+        }
+    }
+
+    /// checks the abstract counterexamples up to the depth on the concrete model, returns
+    /// true if a real counterexample is found, otherwise the visible latchs are refined
+    fn refine(&mut self, depth: usize) -> bool {
+        let invisible: Vec<Var> = self
+            .ts
+            .latchs
+            .iter()
+            .copied()
+            .filter(|l| !self.visible.contains(l))
+            .collect();
+        let mut uts = TransysUnroll::new(&self.ts.abstract_latchs(&self.visible));
+        uts.unroll_to(depth);
+        let acts: Vec<Lit> = invisible.iter().map(|_| uts.new_var().lit()).collect();
+        let mut solver: Box<dyn Satif> = Box::new(satif_cadical::Solver::new());
+        uts.ts.load_init(solver.as_mut());
+        for k in 0..=depth {
+            uts.load_trans(solver.as_mut(), k, true);
+        }
+        for (l, act) in invisible.iter().zip(acts.iter()) {
+            if let Some(i) = self.ts.init_map[*l] {
+                solver.add_clause(&[!*act, l.lit().not_if(!i)]);
+            }
+            let next = self.ts.lit_next(l.lit());
+            for k in 0..depth {
+                let (l, n) = (uts.lit_next(l.lit(), k + 1), uts.lit_next(next, k));
+                solver.add_clause(&[!*act, !l, n]);
+                solver.add_clause(&[!*act, l, !n]);
+            }
+        }
+        let mut core = GHashSet::new();
+        for k in 0..=depth {
+            let mut assump = acts.clone();
+            assump.push(uts.lit_next(uts.ts.bad, k));
+            if solver.solve(&assump) {
+                if self.options.verbose > 0 {
+                    println!("abs: real counterexample in depth {k}");
+                }
+                self.cex = Some((solver, uts, k));
+                return true;
+            }
+            for (l, act) in invisible.iter().zip(acts.iter()) {
+                if solver.unsat_has(*act) {
+                    core.insert(*l);
+                }
+            }
+        }
+        if core.is_empty() {
+            // no abstract counterexample within the depth, fall back to the concrete model
+            core.extend(invisible);
+        }
+        self.visible.extend(core);
+        self.num_refine += 1;
+        if self.options.verbose > 0 {
+            println!(
+                "abs: refinement {}, {} of {} latchs visible",
+                self.num_refine,
+                self.visible.len(),
+                self.ts.latchs.len()
+            );
+        }
+        false
+    }
+}
+
+impl Engine for Localization {
+    fn check(&mut self, budget: &Budget) -> Option<bool> {
+        loop {
+            if budget.exhausted() {
+                return None;
+            }
+            let ts = self.ts.abstract_latchs(&self.visible);
+            // the refined abstraction has fewer behaviors, the lemmas holding in the first
+            // frame of the previous abstraction still hold in its first frame
+            let lemmas = self.ic3.as_ref().map_or(vec![], |ic3| {
+                let mut lemmas = ic3.frame_lemmas();
+                lemmas.retain(|c| c.iter().all(|l| self.ts.is_latch(l.var())));
+                lemmas
+            });
+            let mut ic3 = IC3::new(self.options.clone(), ts, lemmas);
+            let res = ic3.check(budget);
+            let depth = if res == Some(false) {
+                ic3.cex_depth()
+            } else {
+                None
+            };
+            self.ic3 = Some(ic3);
+            match res {
+                Some(true) => return Some(true),
+                None => return None,
+                Some(false) => {
+                    let depth = depth.unwrap();
+                    if self.visible.len() == self.ts.latchs.len() || self.refine(depth) {
+                        return Some(false);
+                    }
+                }
+            }
+        }
+    }
+
+    fn bound(&self) -> Option<usize> {
+        self.ic3.as_ref()?.bound()
+    }
+
+    fn certifaiger(&mut self, aig: &Aig) -> Option<Aig> {
+        self.ic3.as_mut()?.certifaiger(aig)
+    }
+
+    fn witness(&mut self, aig: &Aig) -> Result<String, Error> {
+        let Some((solver, uts, depth)) = self.cex.as_mut() else {
+            // the abstraction is concrete
+            return self
+                .ic3
+                .as_mut()
+                .ok_or(Error::Unsupported("witness"))?
+                .witness(aig);
+        };
+        let mut wit = vec![self.ts.restore_state(|l| solver.sat_value(l))];
+        for k in 0..=*depth {
+            let mut w = Cube::new();
+            for l in self.ts.inputs.iter() {
+                let l = l.lit();
+                if let Some(v) = solver.sat_value(uts.lit_next(l, k)) {
+                    w.push(self.ts.restore(l.not_if(!v)));
+                }
+            }
+            wit.push(w);
+        }
+        witness_encode(aig, &wit)
+    }
+
+    fn invariant(&mut self) -> Option<Vec<Clause>> {
+        self.ic3.as_ref()?.invariant()
+    }
+
+    fn statistic(&mut self) {
+        if let Some(ic3) = self.ic3.as_mut() {
+            ic3.statistic();
+        }
+        println!("abs refinements: {}", self.num_refine);
+    }
+
+    fn report(&mut self, report: &mut Report) {
+        if let Some(ic3) = self.ic3.as_mut() {
+            ic3.report(report);
+        }
+        report.statistic.insert(
+            "abs".to_string(),
+            serde_json::json!({
+                "refinements": self.num_refine,
+                "visible": self.visible.len(),
+                "latchs": self.ts.latchs.len(),
+            }),
+        );
+    }
+}
@@ -1,7 +1,13 @@
 use crate::{
+    budget::Budget,
     options::Options,
+    portfolio::{
+        heartbeat::{self, Progress},
+        share::ShareWorker,
+    },
+    report::{Report, SolveStatistic},
     transys::{unroll::TransysUnroll, Transys},
-    witness_encode, Engine,
+    witness_encode, witness_encode_minimized, Engine, Error,
 };
 use aig::Aig;
 use logic_form::Cube;
@@ -12,6 +18,8 @@ pub struct BMC {
     uts: TransysUnroll,
     options: Options,
     solver: Box<dyn Satif>,
+    share: Option<ShareWorker>,
+    statistic: SolveStatistic,
 }
 
 impl BMC {
@@ -27,9 +35,15 @@ impl BMC {
             uts,
             options,
             solver,
+            share: None,
+            statistic: SolveStatistic::default(),
         }
     }
 
+    pub fn set_share(&mut self, share: ShareWorker) {
+        self.share = Some(share);
+    }
+
     pub fn reset_solver(&mut self) {
         self.solver = if self.options.bmc.bmc_kissat {
             Box::new(satif_kissat::Solver::new())
@@ -41,9 +55,17 @@ impl BMC {
 }
 
 impl Engine for BMC {
-    fn check(&mut self) -> Option<bool> {
+    fn check(&mut self, budget: &Budget) -> Option<bool> {
         let step = self.options.step as usize;
+        // all depths up to the current one are proved, only such a prefix can be shared
+        let mut proved = step == 1;
         for k in (step - 1..).step_by(step) {
+            if budget.exhausted() || budget.depth_exceeded(k) {
+                if self.options.verbose > 0 {
+                    println!("bmc stopped in depth {k}");
+                }
+                return None;
+            }
             self.uts.unroll_to(k);
             let last_bound = if self.options.bmc.bmc_kissat {
                 self.reset_solver();
@@ -64,19 +86,37 @@ impl Engine for BMC {
             if self.options.verbose > 0 {
                 println!("bmc depth: {k}");
             }
-            let r = if let Some(limit) = self.options.bmc.time_limit {
+            heartbeat::beat(Progress::Depth(k));
+            if let Some(share) = &self.share {
+                if share.safe_depth().is_some_and(|d| d >= k) {
+                    continue;
+                }
+            }
+            let limit = self.options.bmc.time_limit.map(Duration::from_secs);
+            let limit = match (limit, budget.remaining()) {
+                (Some(l), Some(r)) => Some(l.min(r)),
+                (l, r) => l.or(r),
+            };
+            let r = if let Some(limit) = limit {
                 let Some(r) = self
-                    .solver
-                    .solve_with_limit(&assump, Duration::from_secs(limit))
+                    .statistic
+                    .solve(|| self.solver.solve_with_limit(&assump, limit))
                 else {
+                    if budget.exhausted() {
+                        if self.options.verbose > 0 {
+                            println!("bmc stopped in depth {k}");
+                        }
+                        return None;
+                    }
                     if self.options.verbose > 0 {
                         println!("bmc solve timeout in depth {k}");
                     }
+                    proved = false;
                     continue;
                 };
                 r
             } else {
-                self.solver.solve(&assump)
+                self.statistic.solve(|| self.solver.solve(&assump))
             };
             if r {
                 if self.options.verbose > 0 {
@@ -84,6 +124,11 @@ impl Engine for BMC {
                 }
                 return Some(false);
             }
+            if let Some(share) = &self.share {
+                if proved {
+                    share.publish_safe_depth(k);
+                }
+            }
             // for s in last_bound..=k {
             //     solver.add_clause(&[!self.uts.lit_next(self.uts.ts.bad, s)]);
             // }
@@ -91,15 +136,24 @@ impl Engine for BMC {
         unreachable!();
     }
 
-    fn witness(&mut self, aig: &Aig) -> String {
-        let mut wit = vec![Cube::new()];
-        for l in self.uts.ts.latchs.iter() {
-            let l = l.lit();
-            if let Some(v) = self.solver.sat_value(l) {
-                wit[0].push(self.uts.ts.restore(l.not_if(!v)));
+    fn bound(&self) -> Option<usize> {
+        Some(self.uts.num_unroll)
+    }
+
+    fn witness(&mut self, aig: &Aig) -> Result<String, Error> {
+        let mut depth = self.uts.num_unroll;
+        let step = self.options.step as usize;
+        if self.options.bmc.min_witness && !self.options.bmc.bmc_kissat && step > 1 {
+            // with step 1 the depths before were checked, otherwise find the shortest
+            // depth in which the bad is reachable
+            let bad = self.uts.ts.bad;
+            match (0..depth).find(|k| self.solver.solve(&[self.uts.lit_next(bad, *k)])) {
+                Some(k) => depth = k,
+                None => assert!(self.solver.solve(&[self.uts.lit_next(bad, depth)])),
             }
         }
-        for k in 0..=self.uts.num_unroll {
+        let mut wit = vec![self.uts.ts.restore_state(|l| self.solver.sat_value(l))];
+        for k in 0..=depth {
             let mut w = Cube::new();
             for l in self.uts.ts.inputs.iter() {
                 let l = l.lit();
@@ -110,6 +164,20 @@ impl Engine for BMC {
             }
             wit.push(w);
         }
-        witness_encode(aig, &wit)
+        if self.options.bmc.min_witness {
+            witness_encode_minimized(aig, &wit)
+        } else {
+            witness_encode(aig, &wit)
+        }
+    }
+
+    fn statistic(&mut self) {
+        println!("{:#?}", self.statistic);
+    }
+
+    fn report(&mut self, report: &mut Report) {
+        report
+            .statistic
+            .insert("bmc".to_string(), self.statistic.json());
     }
 }
@@ -0,0 +1,73 @@
+use crate::options::Options;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// resource budget of a check, cloned budgets share the cancellation token
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    max_depth: Option<usize>,
+    max_frame: Option<usize>,
+}
+
+impl Budget {
+    pub fn new(options: &Options) -> Self {
+        Self {
+            cancelled: Default::default(),
+            deadline: options
+                .budget
+                .time
+                .map(|t| Instant::now() + Duration::from_secs(t)),
+            max_depth: options.budget.depth,
+            max_frame: options.budget.frame,
+        }
+    }
+
+    /// a budget with the same limits and its own cancellation token
+    pub fn child(&self) -> Self {
+        Self {
+            cancelled: Default::default(),
+            ..self.clone()
+        }
+    }
+
+    /// requests all engines sharing this budget to stop at their next safe point
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// remaining wall-clock time, none if unlimited
+    #[inline]
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    /// cancelled or out of time
+    #[inline]
+    pub fn exhausted(&self) -> bool {
+        self.is_cancelled() || self.remaining().is_some_and(|r| r.is_zero())
+    }
+
+    #[inline]
+    pub fn depth_exceeded(&self, depth: usize) -> bool {
+        self.max_depth.is_some_and(|m| depth > m)
+    }
+
+    #[inline]
+    pub fn frame_exceeded(&self, frame: usize) -> bool {
+        self.max_frame.is_some_and(|m| frame > m)
+    }
+}
@@ -0,0 +1,358 @@
+use crate::Error;
+use aig::{Aig, AigEdge, TernarySimulate};
+use giputils::hash::GHashMap;
+use logic_form::{Lbool, Lit, Var};
+use satif::Satif;
+use std::fmt::{self, Display};
+
+/// a cnf encoding of several aig copies in one solver
+struct Encoder {
+    solver: satif_cadical::Solver,
+    num_var: usize,
+    tru: Lit,
+}
+
+/// solver literals of the variables of a loaded aig copy
+struct AigCopy {
+    map: Vec<Lit>,
+}
+
+impl AigCopy {
+    #[inline]
+    fn lit(&self, e: AigEdge) -> Lit {
+        let l = e.to_lit();
+        self.map[Into::<usize>::into(l.var())].not_if(!l.polarity())
+    }
+}
+
+impl Encoder {
+    fn new() -> Self {
+        let mut encoder = Self {
+            solver: satif_cadical::Solver::new(),
+            num_var: 0,
+            tru: Lit::default(),
+        };
+        encoder.tru = encoder.new_lit();
+        encoder.solver.add_clause(&[encoder.tru]);
+        encoder
+    }
+
+    fn new_lit(&mut self) -> Lit {
+        let v = Var::new(self.num_var);
+        self.num_var += 1;
+        self.solver.new_var_to(v);
+        v.lit()
+    }
+
+    #[inline]
+    fn constant(&self, c: bool) -> Lit {
+        self.tru.not_if(!c)
+    }
+
+    /// loads a copy of the aig, nodes in bind are shared with the given literals and
+    /// all other nodes get fresh variables
+    fn load(&mut self, aig: &Aig, bind: &GHashMap<usize, Lit>) -> AigCopy {
+        let cnf = aig.get_cnf();
+        let num_var = Into::<usize>::into(cnf.max_var()) + 1;
+        let mut map = Vec::with_capacity(num_var);
+        for v in 0..num_var {
+            let l = match bind.get(&v) {
+                Some(l) => *l,
+                None if v == 0 => self.constant(false),
+                None => self.new_lit(),
+            };
+            map.push(l);
+        }
+        for cls in cnf.cnf.iter() {
+            let cls: Vec<Lit> = cls
+                .iter()
+                .map(|l| map[Into::<usize>::into(l.var())].not_if(!l.polarity()))
+                .collect();
+            self.solver.add_clause(&cls);
+        }
+        AigCopy { map }
+    }
+
+    /// a fresh literal that is true iff a and b differ
+    fn new_xor(&mut self, a: Lit, b: Lit) -> Lit {
+        let d = self.new_lit();
+        self.solver.add_clause(&[!d, a, b]);
+        self.solver.add_clause(&[!d, !a, !b]);
+        self.solver.add_clause(&[d, !a, b]);
+        self.solver.add_clause(&[d, a, !b]);
+        d
+    }
+
+    fn assert_all(&mut self, lits: impl IntoIterator<Item = Lit>) {
+        for l in lits {
+            self.solver.add_clause(&[l]);
+        }
+    }
+}
+
+fn reset(aig: &Aig, copy: &AigCopy, filter: impl Fn(usize) -> bool) -> Vec<Lit> {
+    aig.latchs
+        .iter()
+        .filter(|l| filter(l.input))
+        .filter_map(|l| l.init.map(|i| copy.lit(AigEdge::from(l.input)).not_if(!i)))
+        .collect()
+}
+
+fn lits(copy: &AigCopy, edges: &[AigEdge]) -> Vec<Lit> {
+    edges.iter().map(|e| copy.lit(*e)).collect()
+}
+
+/// bad edges of the model, outputs are treated as bad properties when certifying
+fn model_bads(model: &Aig) -> Vec<AigEdge> {
+    model
+        .bads
+        .iter()
+        .chain(model.outputs.iter())
+        .copied()
+        .collect()
+}
+
+/// checks a certifaiger safety certificate against the model without docker,
+/// following the reset, transition, property, base and step checks of certifaiger
+pub fn check_certificate(model: &Aig, certificate: &Aig) -> Result<(), Error> {
+    let failed = |check: &str| Err(Error::CertifyFailed(format!("{check} check failed")));
+    let Some(property) = certificate
+        .bads
+        .first()
+        .or(certificate.outputs.first())
+        .copied()
+    else {
+        return Err(Error::CertifyFailed(
+            "certificate has no property".to_string(),
+        ));
+    };
+    // certificate inputs and latches are mapped to aiger literals of the model by
+    // "= lit" symbols
+    let mut shared = GHashMap::new();
+    for (n, s) in certificate.symbols.iter() {
+        if let Some(l) = s.strip_prefix("= ").and_then(|l| l.parse::<usize>().ok()) {
+            shared.insert(*n, l);
+        }
+    }
+    let model_latchs: GHashMap<usize, usize> = model
+        .latchs
+        .iter()
+        .enumerate()
+        .map(|(i, l)| (l.input, i))
+        .collect();
+    let is_shared_latch = |n: usize| {
+        shared
+            .get(&n)
+            .is_some_and(|l| l % 2 == 0 && model_latchs.contains_key(&(l / 2)))
+    };
+    let load_pair = |encoder: &mut Encoder| {
+        let m = encoder.load(model, &GHashMap::new());
+        let bind = shared
+            .iter()
+            .map(|(n, l)| (*n, m.lit(AigEdge::new(l / 2, l % 2 == 1))))
+            .collect();
+        let w = encoder.load(certificate, &bind);
+        (m, w)
+    };
+
+    // reset: R /\ C /\ R'(unshared) => R'(shared) /\ C'
+    let mut encoder = Encoder::new();
+    let (m, w) = load_pair(&mut encoder);
+    encoder.assert_all(reset(model, &m, |_| true));
+    encoder.assert_all(lits(&m, &model.constraints));
+    encoder.assert_all(reset(certificate, &w, |n| !is_shared_latch(n)));
+    let goal: Vec<Lit> = reset(certificate, &w, is_shared_latch)
+        .into_iter()
+        .chain(lits(&w, &certificate.constraints))
+        .map(|l| !l)
+        .collect();
+    if !goal.is_empty() {
+        encoder.solver.add_clause(&goal);
+        if encoder.solver.solve(&[]) {
+            return failed("reset");
+        }
+    }
+
+    // transition: C /\ C' => F'(shared) = F
+    let mut encoder = Encoder::new();
+    let (m, w) = load_pair(&mut encoder);
+    encoder.assert_all(lits(&m, &model.constraints));
+    encoder.assert_all(lits(&w, &certificate.constraints));
+    let mut diff = Vec::new();
+    for l in certificate
+        .latchs
+        .iter()
+        .filter(|l| is_shared_latch(l.input))
+    {
+        let ml = &model.latchs[model_latchs[&(shared[&l.input] / 2)]];
+        let (a, b) = (w.lit(l.next), m.lit(ml.next));
+        diff.push(encoder.new_xor(a, b));
+    }
+    if !diff.is_empty() {
+        encoder.solver.add_clause(&diff);
+        if encoder.solver.solve(&[]) {
+            return failed("transition");
+        }
+    }
+
+    // property: C /\ C' /\ P' => P
+    let mut encoder = Encoder::new();
+    let (m, w) = load_pair(&mut encoder);
+    encoder.assert_all(lits(&m, &model.constraints));
+    encoder.assert_all(lits(&w, &certificate.constraints));
+    encoder.assert_all([!w.lit(property)]);
+    let bads = lits(&m, &model_bads(model));
+    if !bads.is_empty() {
+        encoder.solver.add_clause(&bads);
+        if encoder.solver.solve(&[]) {
+            return failed("property");
+        }
+    }
+
+    // base: R' /\ C' => P'
+    let mut encoder = Encoder::new();
+    let w = encoder.load(certificate, &GHashMap::new());
+    encoder.assert_all(reset(certificate, &w, |_| true));
+    encoder.assert_all(lits(&w, &certificate.constraints));
+    encoder.assert_all([w.lit(property)]);
+    if encoder.solver.solve(&[]) {
+        return failed("base");
+    }
+
+    // step: P' /\ C' /\ F' /\ C'' => P''
+    let mut encoder = Encoder::new();
+    let w0 = encoder.load(certificate, &GHashMap::new());
+    let bind = certificate
+        .latchs
+        .iter()
+        .map(|l| (l.input, w0.lit(l.next)))
+        .collect();
+    let w1 = encoder.load(certificate, &bind);
+    encoder.assert_all([!w0.lit(property), w1.lit(property)]);
+    encoder.assert_all(lits(&w0, &certificate.constraints));
+    encoder.assert_all(lits(&w1, &certificate.constraints));
+    if encoder.solver.solve(&[]) {
+        return failed("step");
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub enum WitnessError {
+    /// the witness text is malformed
+    Parse(String),
+    /// justice witnesses can not be replayed
+    Justice,
+    /// the latch does not start in its initial value
+    Init { latch: usize },
+    /// the constraint does not hold in the step
+    Constraint { step: usize, constraint: usize },
+    /// the bad property is not true in the final step
+    Bad { step: usize, property: usize },
+}
+
+impl Display for WitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WitnessError::Parse(s) => write!(f, "malformed witness: {s}"),
+            WitnessError::Justice => write!(f, "justice witness is not supported"),
+            WitnessError::Init { latch } => {
+                write!(f, "latch {latch} is not in its initial value")
+            }
+            WitnessError::Constraint { step, constraint } => {
+                write!(f, "constraint {constraint} violated in step {step}")
+            }
+            WitnessError::Bad { step, property } => {
+                write!(f, "bad property b{property} is not true in step {step}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for WitnessError {}
+
+pub(crate) fn parse_values(line: &str, len: usize) -> Result<Vec<Lbool>, WitnessError> {
+    if line.len() != len {
+        return Err(WitnessError::Parse(format!(
+            "expected {len} values, found \"{line}\""
+        )));
+    }
+    line.bytes()
+        .map(|c| match c {
+            b'0' => Ok(Lbool::FALSE),
+            b'1' => Ok(Lbool::TRUE),
+            b'x' => Ok(Lbool::NONE),
+            c => Err(WitnessError::Parse(format!("invalid value {}", c as char))),
+        })
+        .collect()
+}
+
+/// replays an aiger witness on the aig with ternary simulation, the constraints must hold
+/// in every step and the claimed bad property must be true in the final step
+pub fn check_witness(aig: &Aig, witness: &str) -> Result<(), WitnessError> {
+    let parse = |s: &str| Err(WitnessError::Parse(s.to_string()));
+    // the state and input lines are empty without latchs or inputs
+    let mut lines = witness
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.starts_with('c'))
+        .skip_while(|l| l.is_empty());
+    if lines.next() != Some("1") {
+        return parse("witness is not a counterexample");
+    }
+    let Some(props) = lines.next() else {
+        return parse("missing property line");
+    };
+    let mut claimed = Vec::new();
+    for p in props.split_whitespace() {
+        if p.starts_with('j') {
+            return Err(WitnessError::Justice);
+        }
+        match p.strip_prefix('b').and_then(|p| p.parse::<usize>().ok()) {
+            Some(p) if p < aig.bads.len() => claimed.push(p),
+            _ => return parse(&format!("invalid property {p}")),
+        }
+    }
+    if claimed.is_empty() {
+        return parse("no property is claimed");
+    }
+    let Some(init) = lines.next() else {
+        return parse("missing initial state");
+    };
+    let mut state = parse_values(init, aig.latchs.len())?;
+    for (i, l) in aig.latchs.iter().enumerate() {
+        if let Some(init) = l.init {
+            if state[i] != Lbool::NONE && state[i] != Lbool::from(init) {
+                return Err(WitnessError::Init { latch: i });
+            }
+            state[i] = Lbool::from(init);
+        }
+    }
+    let frames: Vec<&str> = lines.take_while(|l| *l != ".").collect();
+    if frames.is_empty() {
+        return parse("missing input");
+    }
+    let mut simulate = TernarySimulate::new(aig, state);
+    for (k, frame) in frames.iter().enumerate() {
+        simulate.simulate(parse_values(frame, aig.inputs.len())?);
+        if let Some(c) = aig
+            .constraints
+            .iter()
+            .position(|c| !simulate.value(*c).is_true())
+        {
+            return Err(WitnessError::Constraint {
+                step: k,
+                constraint: c,
+            });
+        }
+    }
+    for p in claimed {
+        if !simulate.value(aig.bads[p]).is_true() {
+            return Err(WitnessError::Bad {
+                step: frames.len() - 1,
+                property: p,
+            });
+        }
+    }
+    Ok(())
+}
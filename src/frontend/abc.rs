@@ -1,6 +1,7 @@
+use crate::path_str;
 use abc_rs::Abc;
 use aig::Aig;
-use std::{env, mem::take, time::Duration};
+use std::{env, fs, io, mem::take, time::Duration};
 
 fn preprocess(f: String) {
     let mut aig = Aig::from_file(&f);
@@ -45,22 +46,63 @@ fn preprocess(f: String) {
     abc_aig.to_file(&f, false);
 }
 
-#[allow(unused)]
-pub fn abc_preprocess(mut aig: Aig) -> Aig {
+fn retime(f: String) {
+    let mut aig = Aig::from_file(&f);
+    let num_constraints = aig.constraints.len();
+    aig.outputs.push(aig.bads[0]);
+    aig.bads.clear();
+    for c in take(&mut aig.constraints) {
+        aig.outputs.push(c);
+    }
+    let mut abc = Abc::new();
+    abc.read_aig(&aig);
+    drop(aig);
+    abc.execute_command("strash; dretime -f; strash;");
+    let mut abc_aig = abc.write_aig();
+    for i in 0..num_constraints {
+        abc_aig.constraints.push(abc_aig.outputs[1 + i]);
+    }
+    abc_aig.bads.push(abc_aig.outputs[0]);
+    abc_aig.outputs.clear();
+    abc_aig.to_file(&f, false);
+}
+
+/// runs the abc job on the aig in a sandboxed process, none if it times out
+fn spawn_abc(aig: &Aig, job: fn(String), timeout: Duration) -> io::Result<Option<Aig>> {
     let dir = match env::var("RIC3_TMP_DIR") {
         Ok(d) => d,
         Err(_) => "/tmp/rIC3".to_string(),
     };
-    let tmpfile = tempfile::NamedTempFile::new_in(dir).unwrap();
-    let path = tmpfile.path().as_os_str().to_str().unwrap();
+    fs::create_dir_all(&dir)?;
+    let tmpfile = tempfile::NamedTempFile::new_in(dir)?;
+    let path = path_str(tmpfile.path())?;
     aig.to_file(path, false);
-    let mut join = procspawn::spawn(path.to_string(), preprocess);
-    if join.join_timeout(Duration::from_secs(5)).is_ok() {
-        aig = Aig::from_file(path);
+    let mut join = procspawn::spawn(path.to_string(), job);
+    if join.join_timeout(timeout).is_ok() {
+        Ok(Some(Aig::from_file(path)))
     } else {
-        println!("abc preprocess timeout");
         let _ = join.kill();
+        Ok(None)
+    }
+}
+
+#[allow(unused)]
+pub fn abc_preprocess(aig: Aig) -> io::Result<Aig> {
+    Ok(
+        spawn_abc(&aig, preprocess, Duration::from_secs(5))?.unwrap_or_else(|| {
+            println!("abc preprocess timeout");
+            aig
+        }),
+    )
+}
+
+/// forward retiming with abc dretime, the bad property and the constraints keep their
+/// cycle by cycle values and the inputs keep their order, so a witness of the retimed aig
+/// is translated back by the original initial state and the same inputs
+pub fn abc_retime(aig: &Aig) -> io::Result<Option<Aig>> {
+    let retimed = spawn_abc(aig, retime, Duration::from_secs(10))?;
+    if retimed.is_none() {
+        println!("abc retime timeout");
     }
-    drop(tmpfile);
-    aig
+    Ok(retimed)
 }
@@ -1,11 +1,18 @@
-use super::abc::abc_preprocess;
+use super::abc::{abc_preprocess, abc_retime};
 use crate::options;
-use aig::Aig;
+use aig::{Aig, AigEdge, TernarySimulate};
 use giputils::hash::{GHashMap, GHashSet};
-use logic_form::Var;
+use logic_form::{Lbool, Lit, Var};
+use std::{io, mem::take};
 
-pub fn aig_preprocess(aig: &Aig, options: &options::Options) -> (Aig, GHashMap<Var, Var>) {
+/// the preprocessed aig with the map to the original variables, and whether its latchs
+/// are retimed
+pub fn aig_preprocess(
+    aig: &Aig,
+    options: &options::Options,
+) -> io::Result<(Aig, GHashMap<Var, Var>, bool)> {
     let (mut aig, mut remap) = aig.coi_refine();
+    let mut is_retimed = false;
     if !(options.preprocess.no_abc
         || matches!(options.engine, options::Engine::IC3) && options.ic3.inn)
     {
@@ -18,7 +25,7 @@ pub fn aig_preprocess(aig: &Aig, options: &options::Options) -> (Aig, GHashMap<V
             remap_retain.insert(l.input.into());
         }
         remap.retain(|x, _| remap_retain.contains(x));
-        aig = abc_preprocess(aig);
+        aig = abc_preprocess(aig)?;
         let remap2;
         (aig, remap2) = aig.coi_refine();
         remap = {
@@ -31,6 +38,179 @@ pub fn aig_preprocess(aig: &Aig, options: &options::Options) -> (Aig, GHashMap<V
             remap_final
         }
     }
+    if retime_enabled(&aig, options) {
+        if let Some(retimed) = abc_retime(&aig)? {
+            if options.verbose > 0 {
+                println!(
+                    "retime: {} latchs retimed into {}",
+                    aig.latchs.len(),
+                    retimed.latchs.len()
+                );
+            }
+            // the retimed latchs have no origin, witnesses start from the original
+            // initial state
+            let mut remap_retimed = GHashMap::new();
+            if let Some(z) = remap.get(&Var::new(0)) {
+                remap_retimed.insert(Var::new(0), *z);
+            }
+            for (x, y) in retimed.inputs.iter().zip(aig.inputs.iter()) {
+                if let Some(z) = remap.get(&Var::from(*y)) {
+                    remap_retimed.insert(Var::from(*x), *z);
+                }
+            }
+            aig = retimed;
+            remap = remap_retimed;
+            is_retimed = true;
+        }
+    }
     aig.constraints.retain(|e| !e.is_constant(true));
-    (aig, remap)
+    Ok((aig, remap, is_retimed))
+}
+
+/// retiming loses the latch origins, so it is skipped if a certificate or invariant is
+/// requested, and it needs initialized latchs to translate witnesses back
+fn retime_enabled(aig: &Aig, options: &options::Options) -> bool {
+    if !options.preprocess.retime
+        || options.certificate.is_some()
+        || options.certify
+        || options.invariant.is_some()
+    {
+        return false;
+    }
+    if aig.latchs.iter().any(|l| l.init.is_none()) {
+        if options.verbose > 0 {
+            println!("Warning: retime skipped, the model has uninitialized latchs");
+        }
+        return false;
+    }
+    true
+}
+
+/// moves the outputs to the bad properties if the aig has no property, returns whether
+/// they are moved
+pub fn outputs_to_bads(aig: &mut Aig) -> bool {
+    if !aig.bads.is_empty() || !aig.justice.is_empty() {
+        return false;
+    }
+    aig.bads = take(&mut aig.outputs);
+    true
+}
+
+/// latchs that keep their initial value in all reachable states, found by ternary
+/// simulation from the initial state with unknown inputs until a fixpoint
+pub fn ternary_constant_latchs(aig: &Aig) -> Vec<Lit> {
+    let mut state: Vec<Lbool> = aig
+        .latchs
+        .iter()
+        .map(|l| l.init.map_or(Lbool::NONE, Lbool::from))
+        .collect();
+    let inputs = vec![Lbool::NONE; aig.inputs.len()];
+    loop {
+        let mut simulate = TernarySimulate::new(aig, state.clone());
+        simulate.simulate(inputs.clone());
+        let mut changed = false;
+        for (s, l) in state.iter_mut().zip(aig.latchs.iter()) {
+            if *s != Lbool::NONE && simulate.value(l.next) != *s {
+                *s = Lbool::NONE;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    aig.latchs
+        .iter()
+        .zip(state)
+        .filter(|(_, s)| *s != Lbool::NONE)
+        .map(|(l, s)| Lit::new(Var::new(l.input), s == Lbool::TRUE))
+        .collect()
+}
+
+/// liveness-to-safety transformation (Biere, Artho, Schuppan 2002). a state is saved
+/// nondeterministically into shadow latchs, each justice property becomes a bad
+/// property that holds when the saved state is revisited and all its justice and
+/// fairness signals have been seen in the loop.
+pub fn liveness_to_safety(aig: &Aig) -> Aig {
+    let mut aig = aig.clone();
+    let latchs = aig.latchs.clone();
+    let save = aig.new_leaf_node();
+    aig.inputs.push(save);
+    let save = AigEdge::from(save);
+    let saved_node = aig.new_leaf_node();
+    let saved = AigEdge::from(saved_node);
+    let in_loop = aig.new_or_node(saved, save);
+    let saving = aig.new_and_node(save, !saved);
+    aig.add_latch(saved_node, in_loop, Some(false));
+    let mut eqs = Vec::new();
+    for l in latchs.iter() {
+        let shadow = aig.new_leaf_node();
+        let keep = aig.new_and_node(!saving, shadow.into());
+        let update = aig.new_and_node(saving, l.input.into());
+        let next = aig.new_or_node(keep, update);
+        aig.add_latch(shadow, next, Some(false));
+        eqs.push(aig.new_eq_node(l.input.into(), shadow.into()));
+    }
+    let looped = aig.new_ands_node(eqs.into_iter());
+    let justice = take(&mut aig.justice);
+    let fairness = take(&mut aig.fairness);
+    for j in justice {
+        let mut accept = vec![saved, looped];
+        for f in j.into_iter().chain(fairness.iter().copied()) {
+            let seen = aig.new_leaf_node();
+            let hit = aig.new_and_node(f, in_loop);
+            let next = aig.new_or_node(seen.into(), hit);
+            aig.add_latch(seen, next, Some(false));
+            accept.push(seen.into());
+        }
+        let bad = aig.new_ands_node(accept.into_iter());
+        aig.bads.push(bad);
+    }
+    aig
+}
+
+/// product miter for sequential equivalence checking, the inputs of the two aigs are
+/// identified by constraints and each pair of mismatching outputs is a bad property.
+/// outputs are compared if the aigs have any, bad properties otherwise
+pub fn miter(a: &Aig, b: &Aig) -> Aig {
+    let outputs = |aig: &Aig| {
+        if aig.outputs.is_empty() {
+            aig.bads.clone()
+        } else {
+            aig.outputs.clone()
+        }
+    };
+    let (oa, ob) = (outputs(a), outputs(b));
+    if a.inputs.len() != b.inputs.len() || oa.len() != ob.len() {
+        panic!(
+            "Error: sec models mismatch, {} inputs and {} outputs against {} inputs and {} outputs.",
+            a.inputs.len(),
+            oa.len(),
+            b.inputs.len(),
+            ob.len()
+        );
+    }
+    if !a.justice.is_empty() || !b.justice.is_empty() {
+        panic!("Error: sec of models with justice properties is not supported.");
+    }
+    let mut miter = a.clone();
+    miter.outputs = oa;
+    miter.bads.clear();
+    let mut b = b.clone();
+    b.outputs = ob;
+    b.bads.clear();
+    let ni = a.inputs.len();
+    let no = miter.outputs.len();
+    miter.merge(&b);
+    for i in 0..ni {
+        let (x, y) = (miter.inputs[i], miter.inputs[i + ni]);
+        let eq = miter.new_eq_node(x.into(), y.into());
+        miter.constraints.push(eq);
+    }
+    let outputs = take(&mut miter.outputs);
+    for i in 0..no {
+        let eq = miter.new_eq_node(outputs[i], outputs[i + no]);
+        miter.bads.push(!eq);
+    }
+    miter
 }
@@ -0,0 +1,748 @@
+use crate::{
+    certify::{check_witness, WitnessError},
+    Error,
+};
+use aig::{Aig, AigEdge};
+use giputils::hash::GHashMap;
+use std::fs;
+
+/// a word-level input or state of the btor2 model, with the aig nodes of its bits (lsb first)
+#[derive(Clone, Debug)]
+pub struct BtorWord {
+    pub id: usize,
+    pub name: String,
+    pub bits: Vec<usize>,
+}
+
+/// btor2 model bit-blasted into an aig
+pub struct Btor {
+    pub aig: Aig,
+    pub inputs: Vec<BtorWord>,
+    pub states: Vec<BtorWord>,
+    /// the hidden input bits of the next values of the states without next function
+    free: GHashMap<usize, Vec<usize>>,
+}
+
+struct BtorLine<'a> {
+    id: usize,
+    op: &'a str,
+    args: Vec<&'a str>,
+}
+
+impl<'a> BtorLine<'a> {
+    fn parse(line: &'a str) -> Result<Option<Self>, Error> {
+        let line = line.split(';').next().unwrap();
+        let mut tokens = line.split_whitespace();
+        let Some(id) = tokens.next() else {
+            return Ok(None);
+        };
+        let invalid = || Error::InvalidInput(format!("invalid btor2 line: {line}"));
+        let id = id.parse().map_err(|_| invalid())?;
+        let op = tokens.next().ok_or_else(invalid)?;
+        Ok(Some(Self {
+            id,
+            op,
+            args: tokens.collect(),
+        }))
+    }
+
+    fn invalid(&self) -> Error {
+        Error::InvalidInput(format!("invalid btor2 line {}", self.id))
+    }
+
+    #[inline]
+    fn arg(&self, i: usize) -> Result<&'a str, Error> {
+        self.args.get(i).copied().ok_or_else(|| self.invalid())
+    }
+
+    #[inline]
+    fn arg_id(&self, i: usize) -> Result<usize, Error> {
+        self.arg(i)?.parse().map_err(|_| self.invalid())
+    }
+
+    #[inline]
+    fn arg_node(&self, i: usize) -> Result<usize, Error> {
+        let id: isize = self.arg(i)?.parse().map_err(|_| self.invalid())?;
+        Ok(id.unsigned_abs())
+    }
+
+    #[inline]
+    fn symbol(&self, i: usize) -> Option<String> {
+        self.args.get(i).map(|s| s.to_string())
+    }
+}
+
+#[inline]
+fn is_const_op(op: &str) -> bool {
+    matches!(op, "const" | "constd" | "consth" | "zero" | "one" | "ones")
+}
+
+/// operators on two bit-vectors of the same sort
+#[inline]
+fn is_binary_op(op: &str) -> bool {
+    matches!(
+        op,
+        "and"
+            | "or"
+            | "xor"
+            | "nand"
+            | "nor"
+            | "xnor"
+            | "iff"
+            | "implies"
+            | "eq"
+            | "neq"
+            | "ult"
+            | "ulte"
+            | "ugt"
+            | "ugte"
+            | "slt"
+            | "slte"
+            | "sgt"
+            | "sgte"
+            | "add"
+            | "sub"
+            | "mul"
+            | "udiv"
+            | "urem"
+            | "sdiv"
+            | "srem"
+            | "smod"
+            | "sll"
+            | "srl"
+            | "sra"
+            | "rol"
+            | "ror"
+    )
+}
+
+#[inline]
+fn bv_const(bits: impl Iterator<Item = bool>) -> Vec<AigEdge> {
+    bits.map(AigEdge::constant_edge).collect()
+}
+
+fn bin_to_bits(s: &str, width: usize) -> Vec<bool> {
+    let mut bits: Vec<bool> = s.chars().rev().map(|c| c == '1').collect();
+    bits.resize(width, false);
+    bits
+}
+
+fn hex_to_bits(s: &str, width: usize) -> Result<Vec<bool>, Error> {
+    let mut bits = Vec::new();
+    for c in s.chars().rev() {
+        let d = c
+            .to_digit(16)
+            .ok_or_else(|| Error::InvalidInput(format!("invalid btor2 hex constant: {s}")))?;
+        for i in 0..4 {
+            bits.push(d & (1 << i) != 0);
+        }
+    }
+    bits.resize(width, false);
+    Ok(bits)
+}
+
+fn dec_to_bits(s: &str, width: usize) -> Result<Vec<bool>, Error> {
+    let mut digits = s
+        .chars()
+        .map(|c| {
+            c.to_digit(10)
+                .ok_or_else(|| Error::InvalidInput(format!("invalid btor2 decimal constant: {s}")))
+        })
+        .collect::<Result<Vec<u32>, Error>>()?;
+    let mut bits = Vec::with_capacity(width);
+    for _ in 0..width {
+        let mut rem = 0;
+        for d in digits.iter_mut() {
+            let cur = rem * 10 + *d;
+            *d = cur / 2;
+            rem = cur % 2;
+        }
+        bits.push(rem == 1);
+    }
+    Ok(bits)
+}
+
+fn neg_bits(bits: Vec<bool>) -> Vec<bool> {
+    let mut carry = true;
+    bits.into_iter()
+        .map(|b| {
+            let r = !b ^ carry;
+            carry &= !b;
+            r
+        })
+        .collect()
+}
+
+#[inline]
+fn xor(aig: &mut Aig, x: AigEdge, y: AigEdge) -> AigEdge {
+    !aig.new_eq_node(x, y)
+}
+
+#[inline]
+fn ite(aig: &mut Aig, c: AigEdge, t: AigEdge, e: AigEdge) -> AigEdge {
+    let t = aig.new_and_node(c, t);
+    let e = aig.new_and_node(!c, e);
+    aig.new_or_node(t, e)
+}
+
+fn bv_not(x: &[AigEdge]) -> Vec<AigEdge> {
+    x.iter().map(|e| !*e).collect()
+}
+
+fn bv_bitwise(
+    aig: &mut Aig,
+    x: &[AigEdge],
+    y: &[AigEdge],
+    f: impl Fn(&mut Aig, AigEdge, AigEdge) -> AigEdge,
+) -> Vec<AigEdge> {
+    assert!(x.len() == y.len());
+    x.iter()
+        .zip(y.iter())
+        .map(|(x, y)| f(aig, *x, *y))
+        .collect()
+}
+
+fn bv_ite(aig: &mut Aig, c: AigEdge, t: &[AigEdge], e: &[AigEdge]) -> Vec<AigEdge> {
+    bv_bitwise(aig, t, e, |aig, t, e| ite(aig, c, t, e))
+}
+
+fn bv_eq(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge]) -> AigEdge {
+    let eqs = bv_bitwise(aig, x, y, |aig, x, y| aig.new_eq_node(x, y));
+    aig.new_ands_node(eqs.into_iter())
+}
+
+fn bv_add(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge], cin: AigEdge) -> (Vec<AigEdge>, AigEdge) {
+    assert!(x.len() == y.len());
+    let mut carry = cin;
+    let mut sum = Vec::with_capacity(x.len());
+    for (x, y) in x.iter().zip(y.iter()) {
+        let xy = xor(aig, *x, *y);
+        sum.push(xor(aig, xy, carry));
+        let g = aig.new_and_node(*x, *y);
+        let p = aig.new_and_node(xy, carry);
+        carry = aig.new_or_node(g, p);
+    }
+    (sum, carry)
+}
+
+/// returns x - y and whether no borrow occurs (x >= y unsigned)
+fn bv_sub(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge]) -> (Vec<AigEdge>, AigEdge) {
+    bv_add(aig, x, &bv_not(y), AigEdge::constant_edge(true))
+}
+
+fn bv_neg(aig: &mut Aig, x: &[AigEdge]) -> Vec<AigEdge> {
+    let zero = bv_const(std::iter::repeat_n(false, x.len()));
+    bv_sub(aig, &zero, x).0
+}
+
+fn bv_ult(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge]) -> AigEdge {
+    !bv_sub(aig, x, y).1
+}
+
+fn bv_slt(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge]) -> AigEdge {
+    let mut x = x.to_vec();
+    let mut y = y.to_vec();
+    let msb = x.len() - 1;
+    x[msb] = !x[msb];
+    y[msb] = !y[msb];
+    bv_ult(aig, &x, &y)
+}
+
+fn bv_mul(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge]) -> Vec<AigEdge> {
+    let width = x.len();
+    let mut acc = bv_const(std::iter::repeat_n(false, width));
+    for i in 0..width {
+        let mut partial = bv_const(std::iter::repeat_n(false, i));
+        for j in 0..width - i {
+            partial.push(aig.new_and_node(x[j], y[i]));
+        }
+        acc = bv_add(aig, &acc, &partial, AigEdge::constant_edge(false)).0;
+    }
+    acc
+}
+
+/// restoring division, division by zero yields all ones quotient and the dividend as remainder
+fn bv_udivrem(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge]) -> (Vec<AigEdge>, Vec<AigEdge>) {
+    let width = x.len();
+    let mut quot = vec![AigEdge::constant_edge(false); width];
+    let mut rem = bv_const(std::iter::repeat_n(false, width));
+    let mut y = y.to_vec();
+    y.push(AigEdge::constant_edge(false));
+    for i in (0..width).rev() {
+        let mut shifted = vec![x[i]];
+        shifted.extend_from_slice(&rem);
+        let (diff, ge) = bv_sub(aig, &shifted, &y);
+        quot[i] = ge;
+        rem = bv_ite(aig, ge, &diff[..width], &shifted[..width]);
+    }
+    (quot, rem)
+}
+
+fn bv_abs(aig: &mut Aig, x: &[AigEdge]) -> Vec<AigEdge> {
+    let neg = bv_neg(aig, x);
+    bv_ite(aig, x[x.len() - 1], &neg, x)
+}
+
+fn bv_sdiv(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge]) -> Vec<AigEdge> {
+    let (sx, sy) = (x[x.len() - 1], y[y.len() - 1]);
+    let ax = bv_abs(aig, x);
+    let ay = bv_abs(aig, y);
+    let (q, _) = bv_udivrem(aig, &ax, &ay);
+    let nq = bv_neg(aig, &q);
+    let s = xor(aig, sx, sy);
+    bv_ite(aig, s, &nq, &q)
+}
+
+fn bv_srem(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge]) -> Vec<AigEdge> {
+    let sx = x[x.len() - 1];
+    let ax = bv_abs(aig, x);
+    let ay = bv_abs(aig, y);
+    let (_, r) = bv_udivrem(aig, &ax, &ay);
+    let nr = bv_neg(aig, &r);
+    bv_ite(aig, sx, &nr, &r)
+}
+
+fn bv_smod(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge]) -> Vec<AigEdge> {
+    let (sx, sy) = (x[x.len() - 1], y[y.len() - 1]);
+    let ax = bv_abs(aig, x);
+    let ay = bv_abs(aig, y);
+    let (_, u) = bv_udivrem(aig, &ax, &ay);
+    let nu = bv_neg(aig, &u);
+    let zero = bv_const(std::iter::repeat_n(false, u.len()));
+    let u_zero = bv_eq(aig, &u, &zero);
+    let (nu_y, _) = bv_add(aig, &nu, y, AigEdge::constant_edge(false));
+    let (u_y, _) = bv_add(aig, &u, y, AigEdge::constant_edge(false));
+    let neg_y = bv_ite(aig, sx, &nu, &u_y);
+    let pos_y = bv_ite(aig, sx, &nu_y, &u);
+    let res = bv_ite(aig, sy, &neg_y, &pos_y);
+    bv_ite(aig, u_zero, &u, &res)
+}
+
+#[derive(Clone, Copy)]
+enum Shift {
+    Sll,
+    Srl,
+    Sra,
+}
+
+fn bv_shift(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge], shift: Shift) -> Vec<AigEdge> {
+    let width = x.len();
+    let fill = match shift {
+        Shift::Sra => x[width - 1],
+        _ => AigEdge::constant_edge(false),
+    };
+    let mut res = x.to_vec();
+    let mut overflow = Vec::new();
+    for (i, b) in y.iter().enumerate() {
+        if i >= usize::BITS as usize - 1 || (1 << i) >= width {
+            overflow.push(*b);
+            continue;
+        }
+        let amount = 1 << i;
+        let shifted: Vec<AigEdge> = (0..width)
+            .map(|j| match shift {
+                Shift::Sll => j.checked_sub(amount).map_or(fill, |k| res[k]),
+                Shift::Srl | Shift::Sra => res.get(j + amount).copied().unwrap_or(fill),
+            })
+            .collect();
+        res = bv_ite(aig, *b, &shifted, &res);
+    }
+    let overflow = aig.new_ors_node(overflow.into_iter());
+    let fills = vec![fill; width];
+    bv_ite(aig, overflow, &fills, &res)
+}
+
+fn bv_rotate(aig: &mut Aig, x: &[AigEdge], y: &[AigEdge], left: bool) -> Vec<AigEdge> {
+    let width = x.len();
+    let amount = if y.len() < usize::BITS as usize && width >= 1 << y.len() {
+        y.to_vec()
+    } else {
+        let w = bv_const((0..y.len()).map(|i| i < usize::BITS as usize && width & (1 << i) != 0));
+        bv_udivrem(aig, y, &w).1
+    };
+    let mut res = x.to_vec();
+    for (i, b) in amount.iter().enumerate() {
+        if i >= usize::BITS as usize - 1 || (1 << i) >= width {
+            break;
+        }
+        let amount = 1 << i;
+        let rotated: Vec<AigEdge> = (0..width)
+            .map(|j| {
+                if left {
+                    res[(j + width - amount) % width]
+                } else {
+                    res[(j + amount) % width]
+                }
+            })
+            .collect();
+        res = bv_ite(aig, *b, &rotated, &res);
+    }
+    res
+}
+
+fn new_word(aig: &mut Aig, width: usize, input: bool) -> Vec<usize> {
+    (0..width)
+        .map(|_| {
+            let n = aig.new_leaf_node();
+            if input {
+                aig.inputs.push(n);
+            }
+            n
+        })
+        .collect()
+}
+
+impl Btor {
+    /// bit-blasts a btor2 file, malformed or unsupported models are rejected
+    pub fn from_file(path: &str) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)?;
+        let lines = content
+            .lines()
+            .filter_map(|l| BtorLine::parse(l).transpose())
+            .collect::<Result<Vec<BtorLine>, Error>>()?;
+        let mut sorts: GHashMap<usize, usize> = GHashMap::new();
+        let mut inits: GHashMap<usize, &BtorLine> = GHashMap::new();
+        let mut nexts: GHashMap<usize, &BtorLine> = GHashMap::new();
+        let mut ops: GHashMap<usize, &str> = GHashMap::new();
+        for l in lines.iter() {
+            ops.insert(l.id, l.op);
+            match l.op {
+                "sort" => match l.arg(0)? {
+                    "bitvec" => {
+                        let width = l.arg_id(1)?;
+                        if width == 0 {
+                            return Err(l.invalid());
+                        }
+                        sorts.insert(l.id, width);
+                    }
+                    sort => {
+                        return Err(Error::InvalidInput(format!(
+                            "rIC3 currently does not support btor2 {sort} sort"
+                        )))
+                    }
+                },
+                "init" => {
+                    inits.insert(l.arg_id(1)?, l);
+                }
+                "next" => {
+                    nexts.insert(l.arg_id(1)?, l);
+                }
+                "fair" | "justice" => {
+                    return Err(Error::InvalidInput(format!(
+                        "rIC3 currently does not support btor2 {} properties",
+                        l.op
+                    )))
+                }
+                _ => (),
+            }
+        }
+        let sort = |l: &BtorLine| -> Result<usize, Error> {
+            sorts.get(&l.arg_id(0)?).copied().ok_or_else(|| l.invalid())
+        };
+        let mut aig = Aig::new();
+        let mut words: GHashMap<usize, Vec<AigEdge>> = GHashMap::new();
+        let mut inputs = Vec::new();
+        for l in lines.iter().filter(|l| l.op == "input") {
+            let bits = new_word(&mut aig, sort(l)?, true);
+            let name = l.symbol(1).unwrap_or(format!("input{}", l.id));
+            inputs.push(BtorWord {
+                id: l.id,
+                name,
+                bits,
+            });
+        }
+        // btor2 states without next function take arbitrary values in each step
+        let mut free = GHashMap::new();
+        for l in lines.iter().filter(|l| l.op == "state") {
+            if !nexts.contains_key(&l.id) {
+                let bits = new_word(&mut aig, sort(l)?, true);
+                free.insert(l.id, bits);
+            }
+        }
+        let mut states = Vec::new();
+        for l in lines.iter().filter(|l| l.op == "state") {
+            let bits = new_word(&mut aig, sort(l)?, false);
+            let name = l.symbol(1).unwrap_or(format!("state{}", l.id));
+            states.push(BtorWord {
+                id: l.id,
+                name,
+                bits,
+            });
+        }
+        // states initialized with non-constant expressions are constrained in the first step
+        let mut non_const = false;
+        for l in inits.values() {
+            non_const |= !ops.get(&l.arg_node(2)?).is_some_and(|op| is_const_op(op));
+        }
+        let first = non_const.then(|| new_word(&mut aig, 1, false)[0]);
+        for w in inputs.iter().chain(states.iter()) {
+            words.insert(w.id, w.bits.iter().map(|b| AigEdge::from(*b)).collect());
+            for (i, b) in w.bits.iter().enumerate() {
+                let symbol = if w.bits.len() == 1 {
+                    w.name.clone()
+                } else {
+                    format!("{}[{i}]", w.name)
+                };
+                aig.set_symbol(*b, &symbol);
+            }
+        }
+        let arg = |words: &GHashMap<usize, Vec<AigEdge>>,
+                   l: &BtorLine,
+                   i: usize|
+         -> Result<Vec<AigEdge>, Error> {
+            let a: isize = l.arg(i)?.parse().map_err(|_| l.invalid())?;
+            let w = words.get(&a.unsigned_abs()).ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "btor2 node {} used before defined",
+                    a.unsigned_abs()
+                ))
+            })?;
+            Ok(if a < 0 { bv_not(w) } else { w.clone() })
+        };
+        for l in lines.iter() {
+            let width = || sort(l);
+            let a = |i| arg(&words, l, i);
+            let mismatch = match l.op {
+                "ite" => a(1)?.len() != 1 || a(2)?.len() != a(3)?.len(),
+                "slice" => l.arg_id(3)? > l.arg_id(2)? || l.arg_id(2)? >= a(1)?.len(),
+                op => is_binary_op(op) && a(1)?.len() != a(2)?.len(),
+            };
+            if mismatch {
+                return Err(Error::InvalidInput(format!(
+                    "btor2 sort mismatch in line {}",
+                    l.id
+                )));
+            }
+            let w = match l.op {
+                "sort" | "input" | "state" | "init" | "next" | "bad" | "constraint" | "output" => {
+                    continue
+                }
+                "const" => bv_const(bin_to_bits(l.arg(1)?, width()?).into_iter()),
+                "constd" => {
+                    let bits = match l.arg(1)?.strip_prefix('-') {
+                        Some(d) => neg_bits(dec_to_bits(d, width()?)?),
+                        None => dec_to_bits(l.arg(1)?, width()?)?,
+                    };
+                    bv_const(bits.into_iter())
+                }
+                "consth" => {
+                    let bits = match l.arg(1)?.strip_prefix('-') {
+                        Some(h) => neg_bits(hex_to_bits(h, width()?)?),
+                        None => hex_to_bits(l.arg(1)?, width()?)?,
+                    };
+                    bv_const(bits.into_iter())
+                }
+                "zero" => bv_const(std::iter::repeat_n(false, width()?)),
+                "one" => bv_const((0..width()?).map(|i| i == 0)),
+                "ones" => bv_const(std::iter::repeat_n(true, width()?)),
+                "not" => bv_not(&a(1)?),
+                "inc" | "dec" => {
+                    let x = a(1)?;
+                    let one = bv_const((0..x.len()).map(|i| i == 0));
+                    if l.op == "inc" {
+                        bv_add(&mut aig, &x, &one, AigEdge::constant_edge(false)).0
+                    } else {
+                        bv_sub(&mut aig, &x, &one).0
+                    }
+                }
+                "neg" => bv_neg(&mut aig, &a(1)?),
+                "redand" => vec![aig.new_ands_node(a(1)?.into_iter())],
+                "redor" => vec![aig.new_ors_node(a(1)?.into_iter())],
+                "redxor" => {
+                    let x = a(1)?;
+                    let mut r = AigEdge::constant_edge(false);
+                    for b in x {
+                        r = xor(&mut aig, r, b);
+                    }
+                    vec![r]
+                }
+                "uext" | "sext" => {
+                    let mut x = a(1)?;
+                    let ext = if l.op == "sext" {
+                        x[x.len() - 1]
+                    } else {
+                        AigEdge::constant_edge(false)
+                    };
+                    x.extend(std::iter::repeat_n(ext, l.arg_id(2)?));
+                    x
+                }
+                "slice" => a(1)?[l.arg_id(3)?..=l.arg_id(2)?].to_vec(),
+                "and" => bv_bitwise(&mut aig, &a(1)?, &a(2)?, |aig, x, y| aig.new_and_node(x, y)),
+                "or" => bv_bitwise(&mut aig, &a(1)?, &a(2)?, |aig, x, y| aig.new_or_node(x, y)),
+                "xor" => bv_bitwise(&mut aig, &a(1)?, &a(2)?, xor),
+                "nand" => bv_not(&bv_bitwise(&mut aig, &a(1)?, &a(2)?, |aig, x, y| {
+                    aig.new_and_node(x, y)
+                })),
+                "nor" => bv_not(&bv_bitwise(&mut aig, &a(1)?, &a(2)?, |aig, x, y| {
+                    aig.new_or_node(x, y)
+                })),
+                "xnor" | "iff" => {
+                    bv_bitwise(&mut aig, &a(1)?, &a(2)?, |aig, x, y| aig.new_eq_node(x, y))
+                }
+                "implies" => bv_bitwise(&mut aig, &a(1)?, &a(2)?, |aig, x, y| {
+                    aig.new_imply_node(x, y)
+                }),
+                "eq" => vec![bv_eq(&mut aig, &a(1)?, &a(2)?)],
+                "neq" => vec![!bv_eq(&mut aig, &a(1)?, &a(2)?)],
+                "ult" => vec![bv_ult(&mut aig, &a(1)?, &a(2)?)],
+                "ulte" => vec![!bv_ult(&mut aig, &a(2)?, &a(1)?)],
+                "ugt" => vec![bv_ult(&mut aig, &a(2)?, &a(1)?)],
+                "ugte" => vec![!bv_ult(&mut aig, &a(1)?, &a(2)?)],
+                "slt" => vec![bv_slt(&mut aig, &a(1)?, &a(2)?)],
+                "slte" => vec![!bv_slt(&mut aig, &a(2)?, &a(1)?)],
+                "sgt" => vec![bv_slt(&mut aig, &a(2)?, &a(1)?)],
+                "sgte" => vec![!bv_slt(&mut aig, &a(1)?, &a(2)?)],
+                "add" => bv_add(&mut aig, &a(1)?, &a(2)?, AigEdge::constant_edge(false)).0,
+                "sub" => bv_sub(&mut aig, &a(1)?, &a(2)?).0,
+                "mul" => bv_mul(&mut aig, &a(1)?, &a(2)?),
+                "udiv" => bv_udivrem(&mut aig, &a(1)?, &a(2)?).0,
+                "urem" => bv_udivrem(&mut aig, &a(1)?, &a(2)?).1,
+                "sdiv" => bv_sdiv(&mut aig, &a(1)?, &a(2)?),
+                "srem" => bv_srem(&mut aig, &a(1)?, &a(2)?),
+                "smod" => bv_smod(&mut aig, &a(1)?, &a(2)?),
+                "sll" => bv_shift(&mut aig, &a(1)?, &a(2)?, Shift::Sll),
+                "srl" => bv_shift(&mut aig, &a(1)?, &a(2)?, Shift::Srl),
+                "sra" => bv_shift(&mut aig, &a(1)?, &a(2)?, Shift::Sra),
+                "rol" => bv_rotate(&mut aig, &a(1)?, &a(2)?, true),
+                "ror" => bv_rotate(&mut aig, &a(1)?, &a(2)?, false),
+                "concat" => {
+                    let mut x = a(2)?;
+                    x.extend(a(1)?);
+                    x
+                }
+                "ite" => {
+                    let c = a(1)?[0];
+                    bv_ite(&mut aig, c, &a(2)?, &a(3)?)
+                }
+                op => {
+                    return Err(Error::InvalidInput(format!(
+                        "rIC3 currently does not support btor2 operator {op}"
+                    )))
+                }
+            };
+            if w.len() != width()? {
+                return Err(Error::InvalidInput(format!(
+                    "btor2 sort mismatch in line {}",
+                    l.id
+                )));
+            }
+            words.insert(l.id, w);
+        }
+        for l in lines.iter() {
+            match l.op {
+                "bad" => {
+                    aig.bads.push(arg(&words, l, 0)?[0]);
+                }
+                "constraint" => {
+                    aig.constraints.push(arg(&words, l, 0)?[0]);
+                }
+                _ => (),
+            }
+        }
+        for s in states.iter() {
+            let next = match nexts.get(&s.id) {
+                Some(n) => arg(&words, n, 2)?,
+                None => free[&s.id].iter().map(|b| AigEdge::from(*b)).collect(),
+            };
+            let init = inits.get(&s.id).map(|i| arg(&words, i, 2)).transpose()?;
+            if next.len() != s.bits.len() || init.as_ref().is_some_and(|i| i.len() != s.bits.len())
+            {
+                return Err(Error::InvalidInput(format!(
+                    "btor2 sort mismatch of state {}",
+                    s.id
+                )));
+            }
+            for (i, b) in s.bits.iter().enumerate() {
+                let init = init.as_ref().and_then(|init| {
+                    if init[i].is_constant(true) {
+                        Some(true)
+                    } else if init[i].is_constant(false) {
+                        Some(false)
+                    } else {
+                        let eq = aig.new_eq_node(AigEdge::from(*b), init[i]);
+                        let c = aig.new_imply_node(AigEdge::from(first.unwrap()), eq);
+                        aig.constraints.push(c);
+                        None
+                    }
+                });
+                aig.add_latch(*b, next[i], init);
+            }
+        }
+        if let Some(first) = first {
+            aig.add_latch(first, AigEdge::constant_edge(false), Some(true));
+        }
+        Ok(Self {
+            aig,
+            inputs,
+            states,
+            free,
+        })
+    }
+
+    /// regroups the bit-level aiger witness of the bit-blasted model into a word-level btor2
+    /// witness. don't care values are 0, as the witness reaches the bad for any value of
+    /// them, the concrete witness is replayed on the aig to make sure. states without next
+    /// function are assigned in each frame from the hidden inputs of the previous step
+    pub fn witness_encode(&self, witness: &str) -> Result<String, WitnessError> {
+        let concrete = witness.replace('x', "0");
+        check_witness(&self.aig, &concrete)?;
+        let lines: Vec<&str> = concrete.lines().collect();
+        let input_pos: GHashMap<usize, usize> = self
+            .aig
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (*n, i))
+            .collect();
+        let latch_pos: GHashMap<usize, usize> = self
+            .aig
+            .latchs
+            .iter()
+            .enumerate()
+            .map(|(i, l)| (l.input, i))
+            .collect();
+        let value = |bits: &[usize], pos: &GHashMap<usize, usize>, line: &str| -> String {
+            bits.iter()
+                .rev()
+                .map(|b| match line.as_bytes().get(pos[b]) {
+                    Some(b'1') => '1',
+                    _ => '0',
+                })
+                .collect()
+        };
+        let mut wit = vec!["sat".to_string(), lines[1].to_string(), "#0".to_string()];
+        for (i, s) in self.states.iter().enumerate() {
+            wit.push(format!(
+                "{i} {} {}#0",
+                value(&s.bits[..], &latch_pos, lines[2]),
+                s.name
+            ));
+        }
+        let frames: Vec<&str> = lines[3..]
+            .iter()
+            .copied()
+            .take_while(|l| *l != ".")
+            .collect();
+        for (k, line) in frames.iter().enumerate() {
+            if k > 0 && !self.free.is_empty() {
+                wit.push(format!("#{k}"));
+                for (i, s) in self.states.iter().enumerate() {
+                    if let Some(bits) = self.free.get(&s.id) {
+                        let v = value(&bits[..], &input_pos, frames[k - 1]);
+                        wit.push(format!("{i} {v} {}#{k}", s.name));
+                    }
+                }
+            }
+            wit.push(format!("@{k}"));
+            for (i, input) in self.inputs.iter().enumerate() {
+                wit.push(format!(
+                    "{i} {} {}@{k}",
+                    value(&input.bits[..], &input_pos, line),
+                    input.name
+                ));
+            }
+        }
+        wit.push(".\n".to_string());
+        Ok(wit.join("\n"))
+    }
+}
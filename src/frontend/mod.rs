@@ -1,2 +1,3 @@
 pub mod abc;
 pub mod aig;
+pub mod btor;
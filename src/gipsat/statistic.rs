@@ -1,4 +1,5 @@
-use giputils::statistic::Average;
+use crate::report::Average;
+use serde_json::{json, Value};
 use std::ops::AddAssign;
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -17,3 +18,14 @@ impl AddAssign for SolverStatistic {
         self.num_simplify_self_subsume += rhs.num_simplify_self_subsume;
     }
 }
+
+impl SolverStatistic {
+    pub fn json(&self) -> Value {
+        json!({
+            "num_solve": self.num_solve,
+            "avg_decide_var": self.avg_decide_var.json(),
+            "num_simplify_subsume": self.num_simplify_subsume,
+            "num_simplify_self_subsume": self.num_simplify_self_subsume,
+        })
+    }
+}
@@ -1,8 +1,14 @@
 use crate::{
+    budget::Budget,
     gipsat::{Solver, SolverStatistic},
     options::Options,
+    portfolio::{
+        heartbeat::{self, Progress},
+        share::ShareWorker,
+    },
+    report::Report,
     transys::{unroll::TransysUnroll, Transys},
-    witness_encode, Engine,
+    witness_encode, Engine, Error,
 };
 use activity::Activity;
 use aig::{Aig, AigEdge};
@@ -12,6 +18,7 @@ use logic_form::{Clause, Cube, Lemma, Var};
 use mic::{DropVarParameter, MicType};
 use proofoblig::{ProofObligation, ProofObligationQueue};
 use rand::{rngs::StdRng, SeedableRng};
+use serde_json::Value;
 use statistic::Statistic;
 use std::time::Instant;
 
@@ -38,6 +45,7 @@ pub struct IC3 {
 
     auxiliary_var: Vec<Var>,
     rng: StdRng,
+    share: Option<ShareWorker>,
 }
 
 impl IC3 {
@@ -46,6 +54,84 @@ impl IC3 {
         self.solvers.len() - 1
     }
 
+    /// clauses of all lemmas of the frames from 1 on over the transys variables, the frames
+    /// are delta encoded so they all hold in the first frame
+    pub fn frame_lemmas(&self) -> Vec<Clause> {
+        self.frame
+            .iter()
+            .skip(1)
+            .flat_map(|f| f.iter())
+            .map(|l| l.iter().map(|l| !*l).collect())
+            .collect()
+    }
+
+    /// the depth of the bad state in the counterexample of an unsafe result
+    pub fn cex_depth(&mut self) -> Option<usize> {
+        if let Some((_, uts)) = self.bmc_solver.as_ref() {
+            return Some(uts.num_unroll);
+        }
+        let mut b = self.obligations.peak()?;
+        let mut depth = 0;
+        while let Some(next) = b.next.clone() {
+            b = next;
+            depth += 1;
+        }
+        Some(depth)
+    }
+
+    /// clauses of the invariant lemmas over the original aig variables, lemmas over
+    /// variables without origin are dropped
+    pub fn lemmas(&self) -> Vec<Clause> {
+        self.frame
+            .invariant()
+            .iter()
+            .filter_map(|l| l.iter().map(|l| self.ts.try_restore(!*l)).collect())
+            .collect()
+    }
+
+    /// clauses of the inductive invariant over the original aig variables, none if a
+    /// lemma is over a variable without origin
+    pub fn invariant(&self) -> Option<Vec<Clause>> {
+        let mut invariant: Vec<Clause> = self
+            .frame
+            .invariant()
+            .iter()
+            .map(|l| l.iter().map(|l| self.ts.try_restore(!*l)).collect())
+            .collect::<Option<_>>()?;
+        invariant.extend(self.ts.merged_clauses());
+        Some(invariant)
+    }
+
+    pub fn set_share(&mut self, share: ShareWorker) {
+        self.share = Some(share);
+    }
+
+    /// publishes the lemmas of the top frame and imports the lemmas of other workers
+    /// that are relatively inductive here, returns true if an invariant is found
+    fn share(&mut self) -> bool {
+        let Some(mut share) = self.share.take() else {
+            return false;
+        };
+        let lemmas: Vec<Clause> = self.frame[self.level()]
+            .iter()
+            .filter_map(|l| l.iter().map(|l| self.ts.try_restore(!*l)).collect())
+            .collect();
+        share.publish_lemmas(lemmas);
+        let lemmas = self.ts.lemmas_from_origin(&share.import_lemmas());
+        self.share = Some(share);
+        for cls in lemmas {
+            let cube = !cls;
+            if self.ts.cube_subsume_init(&cube) {
+                continue;
+            }
+            let (frame, core) = self.push_lemma(0, cube);
+            if frame > 1 && self.add_lemma(frame - 1, core, true, None) {
+                return true;
+            }
+        }
+        false
+    }
+
     fn extend(&mut self) {
         let mut solver = Solver::new(self.options.clone(), Some(self.frame.len()), &self.ts);
         for v in self.auxiliary_var.iter() {
@@ -116,8 +202,22 @@ impl IC3 {
         false
     }
 
-    fn block(&mut self) -> Option<bool> {
+    fn unknown(&mut self) -> Option<bool> {
+        if self.options.verbose > 0 {
+            println!("ic3 stopped in frame {}", self.level());
+        }
+        None
+    }
+
+    /// blocks the proof obligations, false if a counterexample is found, none if an
+    /// invariant is found or the budget is exhausted
+    fn block(&mut self, budget: &Budget) -> Option<bool> {
         while let Some(mut po) = self.obligations.pop(self.level()) {
+            if budget.exhausted() {
+                // keep the obligation, check returns unknown
+                self.add_obligation(po);
+                return None;
+            }
             if po.removed {
                 continue;
             }
@@ -328,7 +428,7 @@ impl IC3 {
                     // check whether the model that causes the problem
                     // can be blocked in the previous frame?
                     // this is not recursively done (not adding may-block)
-                    // but CTG-down is in some sense doing the same as 
+                    // but CTG-down is in some sense doing the same as
                     // having may-blocks
                     let (ctp, _) = self.get_pred(frame_idx + 1, false);
                     if !self.ts.cube_subsume_init(&ctp)
@@ -387,6 +487,7 @@ impl IC3 {
             auxiliary_var: Vec::new(),
             bmc_solver: None,
             rng,
+            share: None,
         };
         res.extend();
         res
@@ -394,11 +495,14 @@ impl IC3 {
 }
 
 impl Engine for IC3 {
-    fn check(&mut self) -> Option<bool> {
+    fn check(&mut self, budget: &Budget) -> Option<bool> {
         loop {
+            if budget.frame_exceeded(self.level()) {
+                return self.unknown();
+            }
             let start = Instant::now();
             loop {
-                match self.block() {
+                match self.block(budget) {
                     Some(false) => {
                         self.statistic.overall_block_time += start.elapsed();
                         self.statistic();
@@ -406,12 +510,19 @@ impl Engine for IC3 {
                     }
                     None => {
                         self.statistic.overall_block_time += start.elapsed();
+                        if budget.exhausted() {
+                            return self.unknown();
+                        }
                         self.statistic();
                         self.verify();
                         return Some(true);
                     }
                     _ => (),
                 }
+                if budget.exhausted() {
+                    self.statistic.overall_block_time += start.elapsed();
+                    return self.unknown();
+                }
                 if let Some((bad, inputs)) = self.get_bad() {
                     let bad = Lemma::new(bad);
                     self.add_obligation(ProofObligation::new(self.level(), bad, inputs, 0, None))
@@ -432,10 +543,14 @@ impl Engine for IC3 {
             }
             self.statistic.overall_block_time += blocked_time;
             self.extend();
+            heartbeat::beat(Progress::Frame {
+                level: self.level(),
+                lemmas: self.frame.iter().map(|f| f.len()).sum(),
+            });
             let start = Instant::now();
             let propagate = self.propagate(None);
             self.statistic.overall_propagate_time += start.elapsed();
-            if propagate {
+            if propagate || self.share() {
                 self.statistic();
                 self.verify();
                 return Some(true);
@@ -443,11 +558,21 @@ impl Engine for IC3 {
         }
     }
 
-    fn certifaiger(&mut self, aig: &Aig) -> Aig {
+    fn bound(&self) -> Option<usize> {
+        Some(self.level())
+    }
+
+    fn invariant(&mut self) -> Option<Vec<Clause>> {
+        IC3::invariant(self)
+    }
+
+    fn certifaiger(&mut self, aig: &Aig) -> Option<Aig> {
         let invariants = self.frame.invariant();
+        let merged = self.ts.merged_clauses().into_iter().map(|c| !c);
         let invariants = invariants
             .iter()
-            .map(|l| Cube::from_iter(l.iter().map(|l| self.ts.restore(*l))));
+            .map(|l| Cube::from_iter(l.iter().map(|l| self.ts.restore(*l))))
+            .chain(merged);
         let mut certifaiger = aig.clone();
         let mut certifaiger_dnf = vec![];
         for cube in invariants {
@@ -461,19 +586,13 @@ impl Engine for IC3 {
         certifaiger.bads.clear();
         certifaiger.outputs.clear();
         certifaiger.outputs.push(invariants);
-        certifaiger
+        Some(certifaiger)
     }
 
-    fn witness(&mut self, aig: &Aig) -> String {
+    fn witness(&mut self, aig: &Aig) -> Result<String, Error> {
         let mut res: Vec<Cube> = vec![Cube::new()];
         if let Some((bmc_solver, uts)) = self.bmc_solver.as_mut() {
-            let mut wit = vec![Cube::new()];
-            for l in uts.ts.latchs.iter() {
-                let l = l.lit();
-                if let Some(v) = bmc_solver.sat_value(l) {
-                    wit[0].push(uts.ts.restore(l.not_if(!v)));
-                }
-            }
+            let mut wit = vec![uts.ts.restore_state(|l| bmc_solver.sat_value(l))];
             for k in 0..=uts.num_unroll {
                 let mut w = Cube::new();
                 for l in uts.ts.inputs.iter() {
@@ -496,12 +615,7 @@ impl Engine for IC3 {
         };
         assump.extend_from_slice(&b.input);
         assert!(self.solvers[0].solve(&assump, vec![]));
-        for l in self.ts.latchs.iter() {
-            let l = l.lit();
-            if let Some(v) = self.solvers[0].sat_value(l) {
-                res[0].push(self.ts.restore(l.not_if(!v)));
-            }
-        }
+        res[0] = self.ts.restore_state(|l| self.solvers[0].sat_value(l));
         let mut b = Some(b);
         while let Some(bad) = b {
             res.push(bad.input.iter().map(|l| self.ts.restore(*l)).collect());
@@ -526,4 +640,25 @@ impl Engine for IC3 {
             println!("{:#?}", self.statistic);
         }
     }
+
+    fn report(&mut self, report: &mut Report) {
+        self.statistic.num_auxiliary_var = self.auxiliary_var.len();
+        let mut statistic = SolverStatistic::default();
+        for s in self.solvers.iter() {
+            statistic += s.statistic;
+        }
+        if let Some(true) = report.verdict {
+            report.invariant_size = Some(self.frame.invariant().len());
+        }
+        let frames: Vec<usize> = self.frame.iter().map(|f| f.len()).collect();
+        report
+            .statistic
+            .insert("frames".to_string(), Value::from(frames));
+        report
+            .statistic
+            .insert("ic3".to_string(), self.statistic.json());
+        report
+            .statistic
+            .insert("solver".to_string(), statistic.json());
+    }
 }
@@ -1,10 +1,11 @@
-use giputils::statistic::{Average, Case, RunningTime, SuccessRate};
+use crate::report::{Average, RunningTime, SuccessRate};
+use serde_json::{json, Value};
 use std::{fmt::Debug, time::Duration};
 
 #[allow(unused)]
 #[derive(Debug, Default)]
 pub struct Statistic {
-    case: Case,
+    case: String,
     time: RunningTime,
 
     pub num_mic: usize,
@@ -37,8 +38,33 @@ impl Statistic {
             case = c;
         }
         Self {
-            case: Case::new(case),
+            case: case.to_string(),
             ..Default::default()
         }
     }
+
+    pub fn json(&self) -> Value {
+        json!({
+            "case": self.case,
+            "time": self.time.secs(),
+            "num_mic": self.num_mic,
+            "avg_mic_cube_len": self.avg_mic_cube_len.json(),
+            "avg_po_cube_len": self.avg_po_cube_len.json(),
+            "mic_drop": self.mic_drop.json(),
+            "num_down": self.num_down,
+            "num_down_sat": self.num_down_sat,
+            "ctp": self.ctp.json(),
+            "num_get_bad": self.num_get_bad,
+            "overall_block_time": self.overall_block_time.as_secs_f64(),
+            "block_get_bad_time": self.block_get_bad_time.as_secs_f64(),
+            "block_get_predecessor_time": self.block_get_predecessor_time.as_secs_f64(),
+            "block_blocked_time": self.block_blocked_time.as_secs_f64(),
+            "block_mic_time": self.block_mic_time.as_secs_f64(),
+            "block_push_time": self.block_push_time.as_secs_f64(),
+            "overall_propagate_time": self.overall_propagate_time.as_secs_f64(),
+            "xor_gen": self.xor_gen.json(),
+            "num_auxiliary_var": self.num_auxiliary_var,
+            "test": self.test.json(),
+        })
+    }
 }
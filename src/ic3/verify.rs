@@ -3,7 +3,7 @@ use crate::transys::{unroll::TransysUnroll, Transys};
 use logic_form::{Clause, Cube, Lemma, Lit};
 use satif::Satif;
 use satif_minisat::Solver;
-use std::{io::Write, fs::File, ops::Deref};
+use std::ops::Deref;
 
 pub fn verify_invariant(ts: &Transys, invariants: &[Lemma]) -> bool {
     let mut solver = Solver::new();
@@ -49,14 +49,6 @@ impl IC3 {
                 invariants.len()
             );
         }
-        let mut file = File::create("inv.cnf").expect("Unable to create inv.cnf");
-        writeln!(&mut file, "{}", invariants.len()).expect("Failed to write to file");
-        for clause in invariants.iter() {
-            for lit in clause.cube().iter() {
-                write!(&mut file, "{} ", lit).expect("Failed to write to file");
-            }
-            writeln!(&mut file,"").expect("Failed to write to file");
-        }
     }
 
     pub fn check_witness(&mut self) -> Option<Lit> {
@@ -0,0 +1,214 @@
+use aig::{Aig, AigEdge};
+use giputils::hash::GHashMap;
+use logic_form::{Clause, Lit, Var};
+use std::ops::Not;
+
+/// an edge of the circuit, the node index with the complement bit
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Edge(u32);
+
+impl Edge {
+    pub const FALSE: Edge = Edge(0);
+    pub const TRUE: Edge = Edge(1);
+
+    #[inline]
+    fn new(node: usize, compl: bool) -> Self {
+        Self(((node as u32) << 1) | compl as u32)
+    }
+
+    #[inline]
+    fn node(self) -> usize {
+        (self.0 >> 1) as usize
+    }
+
+    #[inline]
+    fn compl(self) -> bool {
+        self.0 & 1 == 1
+    }
+}
+
+impl Not for Edge {
+    type Output = Edge;
+
+    #[inline]
+    fn not(self) -> Edge {
+        Edge(self.0 ^ 1)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Node {
+    False,
+    Leaf(Var),
+    And(Edge, Edge),
+}
+
+/// a structurally hashed and-inverter graph over transys variables,
+/// used to build interpolants and reachable state sets
+#[derive(Clone, Debug)]
+pub struct Circuit {
+    nodes: Vec<Node>,
+    leafs: GHashMap<Var, usize>,
+    ands: GHashMap<(Edge, Edge), usize>,
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::False],
+            leafs: GHashMap::new(),
+            ands: GHashMap::new(),
+        }
+    }
+}
+
+impl Circuit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the edge of the literal, variable 0 is the constant false
+    pub fn lit(&mut self, lit: Lit) -> Edge {
+        if lit.var() == Var::new(0) {
+            return Edge::new(0, !lit.polarity());
+        }
+        let node = match self.leafs.get(&lit.var()) {
+            Some(n) => *n,
+            None => {
+                self.nodes.push(Node::Leaf(lit.var()));
+                self.leafs.insert(lit.var(), self.nodes.len() - 1);
+                self.nodes.len() - 1
+            }
+        };
+        Edge::new(node, !lit.polarity())
+    }
+
+    pub fn and(&mut self, a: Edge, b: Edge) -> Edge {
+        if a == Edge::FALSE || b == Edge::FALSE || a == !b {
+            return Edge::FALSE;
+        }
+        if a == Edge::TRUE || a == b {
+            return b;
+        }
+        if b == Edge::TRUE {
+            return a;
+        }
+        let key = if a.0 < b.0 { (a, b) } else { (b, a) };
+        let node = match self.ands.get(&key) {
+            Some(n) => *n,
+            None => {
+                self.nodes.push(Node::And(key.0, key.1));
+                self.ands.insert(key, self.nodes.len() - 1);
+                self.nodes.len() - 1
+            }
+        };
+        Edge::new(node, false)
+    }
+
+    pub fn or(&mut self, a: Edge, b: Edge) -> Edge {
+        !self.and(!a, !b)
+    }
+
+    pub fn ands(&mut self, edges: impl IntoIterator<Item = Edge>) -> Edge {
+        edges.into_iter().fold(Edge::TRUE, |r, e| self.and(r, e))
+    }
+
+    /// nodes in the fanin cone of the roots in topological order
+    fn cone(&self, roots: &[Edge]) -> Vec<usize> {
+        let mut marked = vec![false; self.nodes.len()];
+        let mut stack: Vec<usize> = roots.iter().map(|e| e.node()).collect();
+        while let Some(n) = stack.pop() {
+            if marked[n] {
+                continue;
+            }
+            marked[n] = true;
+            if let Node::And(a, b) = self.nodes[n] {
+                stack.push(a.node());
+                stack.push(b.node());
+            }
+        }
+        (0..self.nodes.len()).filter(|n| marked[*n]).collect()
+    }
+
+    /// copies the cone of the edge into another circuit, leafs are replaced by the given literals
+    pub fn copy(&self, e: Edge, into: &mut Circuit, mut leaf: impl FnMut(Var) -> Lit) -> Edge {
+        let mut map = vec![Edge::FALSE; self.nodes.len()];
+        let edge = |map: &[Edge], e: Edge| {
+            if e.compl() {
+                !map[e.node()]
+            } else {
+                map[e.node()]
+            }
+        };
+        for n in self.cone(&[e]) {
+            map[n] = match self.nodes[n] {
+                Node::False => Edge::FALSE,
+                Node::Leaf(v) => into.lit(leaf(v)),
+                Node::And(a, b) => {
+                    let (a, b) = (edge(&map, a), edge(&map, b));
+                    into.and(a, b)
+                }
+            };
+        }
+        edge(&map, e)
+    }
+
+    /// tseitin encoding of the cone of the roots, returns the literals of the roots and
+    /// the clauses, leafs are mapped to the given literals
+    pub fn cnf(
+        &self,
+        roots: &[Edge],
+        mut leaf: impl FnMut(Var) -> Lit,
+        mut new_var: impl FnMut() -> Var,
+    ) -> (Vec<Lit>, Vec<Clause>) {
+        let mut map = vec![Lit::default(); self.nodes.len()];
+        let mut cnf = Vec::new();
+        for n in self.cone(roots) {
+            map[n] = match self.nodes[n] {
+                Node::False => {
+                    let f = new_var().lit();
+                    cnf.push(Clause::from([!f]));
+                    f
+                }
+                Node::Leaf(v) => leaf(v),
+                Node::And(a, b) => {
+                    let a = map[a.node()].not_if(a.compl());
+                    let b = map[b.node()].not_if(b.compl());
+                    let o = new_var().lit();
+                    cnf.push(Clause::from([!o, a]));
+                    cnf.push(Clause::from([!o, b]));
+                    cnf.push(Clause::from([o, !a, !b]));
+                    o
+                }
+            };
+        }
+        let roots = roots
+            .iter()
+            .map(|e| map[e.node()].not_if(e.compl()))
+            .collect();
+        (roots, cnf)
+    }
+
+    /// adds the cone of the edge to the aig, leafs are mapped to the given aig edges
+    pub fn to_aig(&self, e: Edge, aig: &mut Aig, mut leaf: impl FnMut(Var) -> AigEdge) -> AigEdge {
+        let mut map = vec![AigEdge::new(0, false); self.nodes.len()];
+        let edge = |map: &[AigEdge], e: Edge| {
+            if e.compl() {
+                !map[e.node()]
+            } else {
+                map[e.node()]
+            }
+        };
+        for n in self.cone(&[e]) {
+            map[n] = match self.nodes[n] {
+                Node::False => AigEdge::new(0, false),
+                Node::Leaf(v) => leaf(v),
+                Node::And(a, b) => {
+                    let (a, b) = (edge(&map, a), edge(&map, b));
+                    aig.new_and_node(a, b)
+                }
+            };
+        }
+        edge(&map, e)
+    }
+}
@@ -0,0 +1,647 @@
+use super::circuit::{Circuit, Edge};
+use crate::budget::Budget;
+use logic_form::{Lit, Var};
+use std::mem::take;
+
+struct ItpClause {
+    lits: Vec<Lit>,
+    /// partial interpolant of the clause
+    itp: Edge,
+    learnt: bool,
+    /// deleted learnt clauses are dropped from the watches lazily
+    deleted: bool,
+}
+
+#[inline]
+fn code(lit: Lit) -> usize {
+    Into::<usize>::into(lit.var()) * 2 + lit.polarity() as usize
+}
+
+/// max-heap of variables by activity, a variable is at most once in the heap
+#[derive(Default)]
+struct VarHeap {
+    heap: Vec<usize>,
+    pos: Vec<Option<usize>>,
+}
+
+impl VarHeap {
+    fn reserve(&mut self, n: usize) {
+        self.pos.resize(n, None);
+    }
+
+    fn up(&mut self, mut idx: usize, activity: &[f64]) {
+        let v = self.heap[idx];
+        while idx != 0 {
+            let pidx = (idx - 1) >> 1;
+            if activity[self.heap[pidx]] >= activity[v] {
+                break;
+            }
+            self.heap[idx] = self.heap[pidx];
+            self.pos[self.heap[idx]] = Some(idx);
+            idx = pidx;
+        }
+        self.heap[idx] = v;
+        self.pos[v] = Some(idx);
+    }
+
+    fn down(&mut self, mut idx: usize, activity: &[f64]) {
+        let v = self.heap[idx];
+        loop {
+            let left = (idx << 1) + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.heap.len()
+                && activity[self.heap[right]] > activity[self.heap[left]]
+            {
+                right
+            } else {
+                left
+            };
+            if activity[v] >= activity[self.heap[child]] {
+                break;
+            }
+            self.heap[idx] = self.heap[child];
+            self.pos[self.heap[idx]] = Some(idx);
+            idx = child;
+        }
+        self.heap[idx] = v;
+        self.pos[v] = Some(idx);
+    }
+
+    fn push(&mut self, v: usize, activity: &[f64]) {
+        if self.pos[v].is_some() {
+            return;
+        }
+        self.heap.push(v);
+        self.up(self.heap.len() - 1, activity);
+    }
+
+    /// moves the variable up after its activity is increased
+    fn update(&mut self, v: usize, activity: &[f64]) {
+        if let Some(idx) = self.pos[v] {
+            self.up(idx, activity);
+        }
+    }
+
+    fn pop(&mut self, activity: &[f64]) -> Option<usize> {
+        let v = *self.heap.first()?;
+        let last = self.heap.pop().unwrap();
+        self.pos[v] = None;
+        if !self.heap.is_empty() {
+            self.heap[0] = last;
+            self.down(0, activity);
+        }
+        Some(v)
+    }
+}
+
+/// a cdcl solver that logs the resolution proof as mcmillan partial interpolants,
+/// clauses belong to the a or the b part of an unsatisfiable a and b
+pub struct ItpSolver {
+    clauses: Vec<ItpClause>,
+    /// clauses watching the literal, visited when the literal becomes false
+    watches: Vec<Vec<usize>>,
+    value: Vec<Option<bool>>,
+    level: Vec<usize>,
+    reason: Vec<Option<usize>>,
+    in_a: Vec<bool>,
+    in_b: Vec<bool>,
+    trail: Vec<Lit>,
+    trail_lim: Vec<usize>,
+    qhead: usize,
+    activity: Vec<f64>,
+    var_inc: f64,
+    heap: VarHeap,
+    phase: Vec<bool>,
+    units: Vec<usize>,
+    /// the part of the added clauses, their partial interpolants are set when solving
+    /// as the shared variables are known then
+    part_a: Vec<bool>,
+    /// interpolant of the empty clause
+    empty: Option<Edge>,
+    num_learnt: usize,
+    max_learnt: usize,
+    pub circuit: Circuit,
+}
+
+impl ItpSolver {
+    pub fn new() -> Self {
+        Self {
+            clauses: Vec::new(),
+            watches: Vec::new(),
+            value: Vec::new(),
+            level: Vec::new(),
+            reason: Vec::new(),
+            in_a: Vec::new(),
+            in_b: Vec::new(),
+            trail: Vec::new(),
+            trail_lim: Vec::new(),
+            qhead: 0,
+            activity: Vec::new(),
+            var_inc: 1.0,
+            heap: VarHeap::default(),
+            phase: Vec::new(),
+            units: Vec::new(),
+            part_a: Vec::new(),
+            empty: None,
+            num_learnt: 0,
+            max_learnt: 0,
+            circuit: Circuit::new(),
+        }
+    }
+
+    fn reserve(&mut self, var: Var) {
+        let n = Into::<usize>::into(var) + 1;
+        if self.value.len() >= n {
+            return;
+        }
+        let old = self.value.len();
+        self.value.resize(n, None);
+        self.level.resize(n, 0);
+        self.reason.resize(n, None);
+        self.in_a.resize(n, false);
+        self.in_b.resize(n, false);
+        self.activity.resize(n, 0.0);
+        self.phase.resize(n, false);
+        self.watches.resize(n * 2, Vec::new());
+        self.heap.reserve(n);
+        for v in old..n {
+            self.heap.push(v, &self.activity);
+        }
+    }
+
+    #[inline]
+    fn lit_value(&self, lit: Lit) -> Option<bool> {
+        self.value[Into::<usize>::into(lit.var())].map(|v| v == lit.polarity())
+    }
+
+    #[inline]
+    fn decision_level(&self) -> usize {
+        self.trail_lim.len()
+    }
+
+    /// adds a clause of the a part if a is true, of the b part otherwise,
+    /// all clauses have to be added before the only solve
+    pub fn add_clause(&mut self, clause: &[Lit], a: bool) {
+        let mut lits = clause.to_vec();
+        lits.sort();
+        lits.dedup();
+        if lits.windows(2).any(|w| w[0] == !w[1]) {
+            return;
+        }
+        for l in lits.iter() {
+            self.reserve(l.var());
+            let v: usize = l.var().into();
+            if a {
+                self.in_a[v] = true;
+            } else {
+                self.in_b[v] = true;
+            }
+        }
+        self.push_clause(lits, if a { Edge::FALSE } else { Edge::TRUE }, false);
+        self.part_a.push(a);
+    }
+
+    fn push_clause(&mut self, lits: Vec<Lit>, itp: Edge, learnt: bool) -> usize {
+        let id = self.clauses.len();
+        match lits.len() {
+            0 => {
+                if self.empty.is_none() {
+                    self.empty = Some(itp);
+                }
+            }
+            1 => self.units.push(id),
+            _ => {
+                self.watches[code(lits[0])].push(id);
+                self.watches[code(lits[1])].push(id);
+            }
+        }
+        self.clauses.push(ItpClause {
+            lits,
+            itp,
+            learnt,
+            deleted: false,
+        });
+        id
+    }
+
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) {
+        let v: usize = lit.var().into();
+        self.value[v] = Some(lit.polarity());
+        self.level[v] = self.decision_level();
+        self.reason[v] = reason;
+        self.trail.push(lit);
+    }
+
+    /// returns the conflicting clause
+    fn propagate(&mut self) -> Option<usize> {
+        while self.qhead < self.trail.len() {
+            let f = !self.trail[self.qhead];
+            self.qhead += 1;
+            let ws = take(&mut self.watches[code(f)]);
+            let mut keep = Vec::with_capacity(ws.len());
+            let mut conflict = None;
+            for (i, c) in ws.iter().copied().enumerate() {
+                if conflict.is_some() {
+                    keep.extend_from_slice(&ws[i..]);
+                    break;
+                }
+                if self.clauses[c].deleted {
+                    continue;
+                }
+                if self.clauses[c].lits[0] == f {
+                    self.clauses[c].lits.swap(0, 1);
+                }
+                let first = self.clauses[c].lits[0];
+                if self.lit_value(first) == Some(true) {
+                    keep.push(c);
+                    continue;
+                }
+                let len = self.clauses[c].lits.len();
+                if let Some(k) =
+                    (2..len).find(|k| self.lit_value(self.clauses[c].lits[*k]) != Some(false))
+                {
+                    self.clauses[c].lits.swap(1, k);
+                    let w = self.clauses[c].lits[1];
+                    self.watches[code(w)].push(c);
+                    continue;
+                }
+                keep.push(c);
+                if self.lit_value(first) == Some(false) {
+                    conflict = Some(c);
+                } else {
+                    self.enqueue(first, Some(c));
+                }
+            }
+            self.watches[code(f)] = keep;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// resolves the partial interpolants on the pivot
+    fn resolve(&mut self, itp: Edge, other: Edge, pivot: Var) -> Edge {
+        let v: usize = pivot.into();
+        if self.in_a[v] && !self.in_b[v] {
+            self.circuit.or(itp, other)
+        } else {
+            self.circuit.and(itp, other)
+        }
+    }
+
+    fn bump(&mut self, v: usize) {
+        self.activity[v] += self.var_inc;
+        if self.activity[v] > 1e100 {
+            for a in self.activity.iter_mut() {
+                *a *= 1e-100;
+            }
+            self.var_inc *= 1e-100;
+        }
+        // rescaling keeps the order of the heap
+        self.heap.update(v, &self.activity);
+    }
+
+    /// resolves the marked level zero literals out of the interpolant with their reasons
+    fn resolve_level_zero(&mut self, mut itp: Edge, seen: &mut [bool]) -> Edge {
+        for i in (0..self.trail.len()).rev() {
+            let l = self.trail[i];
+            let v: usize = l.var().into();
+            if self.level[v] > 0 || !seen[v] {
+                continue;
+            }
+            seen[v] = false;
+            let r = self.reason[v].unwrap();
+            for q in self.clauses[r].lits[1..].iter() {
+                seen[Into::<usize>::into(q.var())] = true;
+            }
+            itp = self.resolve(itp, self.clauses[r].itp, l.var());
+        }
+        itp
+    }
+
+    /// first uip conflict analysis, returns the learnt clause with the asserting literal
+    /// first and its partial interpolant
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, Edge) {
+        let mut seen = vec![false; self.value.len()];
+        let mut zero = vec![false; self.value.len()];
+        let mut learnt = vec![Lit::default()];
+        let mut itp = self.clauses[conflict].itp;
+        let mut path = 0;
+        let mut index = self.trail.len();
+        let mut c = conflict;
+        let mut p: Option<Lit> = None;
+        loop {
+            let skip = if p.is_some() { 1 } else { 0 };
+            for j in skip..self.clauses[c].lits.len() {
+                let q = self.clauses[c].lits[j];
+                let v: usize = q.var().into();
+                if seen[v] || zero[v] {
+                    continue;
+                }
+                if self.level[v] == 0 {
+                    zero[v] = true;
+                    continue;
+                }
+                seen[v] = true;
+                self.bump(v);
+                if self.level[v] == self.decision_level() {
+                    path += 1;
+                } else {
+                    learnt.push(q);
+                }
+            }
+            loop {
+                index -= 1;
+                if seen[Into::<usize>::into(self.trail[index].var())] {
+                    break;
+                }
+            }
+            let l = self.trail[index];
+            let v: usize = l.var().into();
+            seen[v] = false;
+            path -= 1;
+            p = Some(l);
+            if path == 0 {
+                break;
+            }
+            c = self.reason[v].unwrap();
+            itp = self.resolve(itp, self.clauses[c].itp, l.var());
+        }
+        learnt[0] = !p.unwrap();
+        let itp = self.resolve_level_zero(itp, &mut zero);
+        self.var_inc *= 1.05;
+        (learnt, itp)
+    }
+
+    /// the clause is the reason of its first literal, level zero reasons stay locked as
+    /// the final interpolant is resolved with them
+    fn locked(&self, c: usize) -> bool {
+        let v: usize = self.clauses[c].lits[0].var().into();
+        self.reason[v] == Some(c)
+    }
+
+    /// deletes the longer half of the unlocked learnt clauses
+    fn reduce(&mut self) {
+        let mut learnts: Vec<usize> = (0..self.clauses.len())
+            .filter(|c| self.clauses[*c].learnt && !self.clauses[*c].deleted && !self.locked(*c))
+            .collect();
+        learnts.sort_by_key(|c| std::cmp::Reverse(self.clauses[*c].lits.len()));
+        for c in learnts[..learnts.len() / 2].iter() {
+            self.clauses[*c].deleted = true;
+            self.clauses[*c].lits = Vec::new();
+        }
+        self.num_learnt -= learnts.len() / 2;
+        self.max_learnt += self.max_learnt / 10;
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.decision_level() <= level {
+            return;
+        }
+        let lim = self.trail_lim[level];
+        for l in self.trail.drain(lim..) {
+            let v: usize = l.var().into();
+            self.phase[v] = l.polarity();
+            self.value[v] = None;
+            self.reason[v] = None;
+            self.heap.push(v, &self.activity);
+        }
+        self.trail_lim.truncate(level);
+        self.qhead = self.trail.len();
+    }
+
+    fn decide(&mut self) -> Option<Lit> {
+        while let Some(v) = self.heap.pop(&self.activity) {
+            if self.value[v].is_none() {
+                return Some(Var::new(v).lit().not_if(!self.phase[v]));
+            }
+        }
+        None
+    }
+
+    /// the interpolant of the empty clause derived from a conflict at level zero
+    fn final_conflict(&mut self, conflict: usize) -> Edge {
+        let mut seen = vec![false; self.value.len()];
+        for q in self.clauses[conflict].lits.iter() {
+            seen[Into::<usize>::into(q.var())] = true;
+        }
+        self.resolve_level_zero(self.clauses[conflict].itp, &mut seen)
+    }
+
+    /// none if the budget is exhausted
+    pub fn solve(&mut self, budget: &Budget) -> Option<bool> {
+        for (c, a) in take(&mut self.part_a).into_iter().enumerate() {
+            if !a {
+                continue;
+            }
+            // the shared literals of an a clause
+            for j in 0..self.clauses[c].lits.len() {
+                let l = self.clauses[c].lits[j];
+                if self.in_b[Into::<usize>::into(l.var())] {
+                    let e = self.circuit.lit(l);
+                    self.clauses[c].itp = self.circuit.or(self.clauses[c].itp, e);
+                }
+            }
+        }
+        if self.empty.is_some() {
+            return Some(false);
+        }
+        for c in take(&mut self.units) {
+            let l = self.clauses[c].lits[0];
+            match self.lit_value(l) {
+                Some(true) => (),
+                Some(false) => {
+                    let v: usize = l.var().into();
+                    let r = self.reason[v].unwrap();
+                    let itp = self.resolve(self.clauses[c].itp, self.clauses[r].itp, l.var());
+                    let mut seen = vec![false; self.value.len()];
+                    for q in self.clauses[r].lits[1..].iter() {
+                        seen[Into::<usize>::into(q.var())] = true;
+                    }
+                    self.empty = Some(self.resolve_level_zero(itp, &mut seen));
+                    return Some(false);
+                }
+                None => self.enqueue(l, Some(c)),
+            }
+        }
+        let mut num_conflict = 0usize;
+        let mut restart = 100usize;
+        self.max_learnt = self.clauses.len() / 3 + 1000;
+        loop {
+            if let Some(conflict) = self.propagate() {
+                if self.decision_level() == 0 {
+                    self.empty = Some(self.final_conflict(conflict));
+                    return Some(false);
+                }
+                num_conflict += 1;
+                if num_conflict % 64 == 0 && budget.exhausted() {
+                    return None;
+                }
+                let (learnt, itp) = self.analyze(conflict);
+                let level = learnt[1..]
+                    .iter()
+                    .map(|l| self.level[Into::<usize>::into(l.var())])
+                    .max()
+                    .unwrap_or(0);
+                self.backtrack(level);
+                let mut learnt = learnt;
+                if let Some(i) = (1..learnt.len())
+                    .find(|i| self.level[Into::<usize>::into(learnt[*i].var())] == level)
+                {
+                    learnt.swap(1, i);
+                }
+                let asserting = learnt[0];
+                let id = self.push_clause(learnt, itp, true);
+                self.units.retain(|u| *u != id);
+                self.num_learnt += 1;
+                self.enqueue(asserting, Some(id));
+                if num_conflict >= restart {
+                    restart += restart / 2;
+                    self.backtrack(0);
+                    if self.num_learnt >= self.max_learnt {
+                        self.reduce();
+                    }
+                }
+            } else {
+                let Some(d) = self.decide() else {
+                    return Some(true);
+                };
+                self.trail_lim.push(self.trail.len());
+                self.enqueue(d, None);
+            }
+        }
+    }
+
+    /// the interpolant of the a and b parts after an unsat solve,
+    /// its leafs are the variables shared by a and b
+    pub fn interpolant(&self) -> Edge {
+        self.empty.unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ItpSolver;
+    use crate::budget::Budget;
+    use logic_form::{Clause, Lit, Var};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use satif::Satif;
+
+    fn lit(l: i32) -> Lit {
+        Var::new(l.unsigned_abs() as usize).lit().not_if(l < 0)
+    }
+
+    fn clauses(cnf: &[Vec<i32>]) -> Vec<Clause> {
+        cnf.iter()
+            .map(|c| c.iter().map(|l| lit(*l)).collect())
+            .collect()
+    }
+
+    fn satisfiable(cnf: &[Clause], assump: &[Lit]) -> bool {
+        let mut solver = satif_cadical::Solver::new();
+        let max_var = cnf.iter().flat_map(|c| c.iter()).map(|l| l.var()).max();
+        solver.new_var_to(max_var.unwrap_or(Var::new(1)));
+        for c in cnf.iter() {
+            solver.add_clause(c);
+        }
+        solver.solve(assump)
+    }
+
+    /// solves a and b, for unsat checks that a implies the interpolant, the interpolant
+    /// and b are unsat and the interpolant is over the shared variables
+    fn check(a: &[Clause], b: &[Clause]) -> bool {
+        let mut solver = ItpSolver::new();
+        for c in a.iter() {
+            solver.add_clause(c, true);
+        }
+        for c in b.iter() {
+            solver.add_clause(c, false);
+        }
+        let ab: Vec<Clause> = a.iter().chain(b.iter()).cloned().collect();
+        let res = solver.solve(&Budget::default()).unwrap();
+        assert_eq!(res, satisfiable(&ab, &[]));
+        if res {
+            return true;
+        }
+        let vars = |cnf: &[Clause]| -> Vec<Var> {
+            cnf.iter().flat_map(|c| c.iter()).map(|l| l.var()).collect()
+        };
+        let (va, vb) = (vars(a), vars(b));
+        let mut max_var = va.iter().chain(vb.iter()).copied().max().unwrap();
+        let (itp, cnf) = solver.circuit.cnf(
+            &[solver.interpolant()],
+            |v| {
+                assert!(va.contains(&v) && vb.contains(&v));
+                v.lit()
+            },
+            || {
+                max_var += 1;
+                max_var
+            },
+        );
+        let a_itp: Vec<Clause> = a.iter().chain(cnf.iter()).cloned().collect();
+        assert!(!satisfiable(&a_itp, &[!itp[0]]));
+        let itp_b: Vec<Clause> = b.iter().chain(cnf.iter()).cloned().collect();
+        assert!(!satisfiable(&itp_b, &[itp[0]]));
+        false
+    }
+
+    #[test]
+    fn small_interpolants() {
+        // x1 and x1 -> x2 against !x2
+        assert!(!check(
+            &clauses(&[vec![1], vec![-1, 2]]),
+            &clauses(&[vec![-2]])
+        ));
+        // x1 = x2 and x2 = x3 against x1 != x3 through the shared x2 only
+        assert!(!check(
+            &clauses(&[vec![-1, 2], vec![1, -2], vec![1]]),
+            &clauses(&[vec![-2, 3], vec![2, -3], vec![-3]])
+        ));
+        // a alone is unsat
+        assert!(!check(
+            &clauses(&[vec![1, 2], vec![-1], vec![-2]]),
+            &clauses(&[vec![2, 3]])
+        ));
+        // b alone is unsat
+        assert!(!check(
+            &clauses(&[vec![1, 2]]),
+            &clauses(&[vec![2, 3], vec![-2], vec![-3]])
+        ));
+        assert!(check(&clauses(&[vec![1, 2]]), &clauses(&[vec![-2, 3]])));
+    }
+
+    /// a random 3-cnf over the 12 variables from lo
+    fn random_cnf(rng: &mut StdRng, lo: i32) -> Vec<Clause> {
+        let len = rng.random_range(20..45);
+        (0..len)
+            .map(|_| {
+                (0..3)
+                    .map(|_| {
+                        let l: i32 = rng.random_range(lo..lo + 12);
+                        lit(if rng.random_bool(0.5) { l } else { -l })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn random_interpolants() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut num_unsat = 0;
+        for _ in 0..300 {
+            // a over 1..=12 and b over 7..=18, 7..=12 are shared
+            let a = random_cnf(&mut rng, 1);
+            let b = random_cnf(&mut rng, 7);
+            if !check(&a, &b) {
+                num_unsat += 1;
+            }
+        }
+        assert!(num_unsat > 0);
+    }
+}
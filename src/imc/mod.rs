@@ -0,0 +1,255 @@
+mod circuit;
+mod itp;
+
+use crate::{
+    budget::Budget,
+    options::Options,
+    portfolio::heartbeat::{self, Progress},
+    transys::{unroll::TransysUnroll, Transys},
+    witness_encode, Engine, Error,
+};
+use aig::{Aig, AigEdge};
+use circuit::{Circuit, Edge};
+use itp::ItpSolver;
+use logic_form::{Cube, Lit, Var};
+use satif::Satif;
+
+/// interpolation based model checking (McMillan 2003). the reachable states are
+/// over-approximated by interpolants of bounded unrollings until a fixpoint is reached,
+/// a counterexample from the initial states increases the bound
+pub struct IMC {
+    uts: TransysUnroll,
+    options: Options,
+    /// state sets over the latchs, the initial states and the reachable states of the fixpoint
+    circuit: Circuit,
+    init: Edge,
+    invariant: Option<Edge>,
+    /// bmc solver of a counterexample with its depth
+    cex: Option<(Box<dyn Satif>, usize)>,
+}
+
+impl IMC {
+    pub fn new(options: Options, ts: Transys) -> Self {
+        let uts = TransysUnroll::new(&ts);
+        let mut circuit = Circuit::new();
+        let init: Vec<Edge> = ts.init.iter().map(|l| circuit.lit(*l)).collect();
+        let init = circuit.ands(init);
+        Self {
+            uts,
+            options,
+            circuit,
+            init,
+            invariant: None,
+            cex: None,
+        }
+    }
+
+    /// the states reachable in one step from r, over-approximated by the interpolant of
+    /// r and the first step against the bad states within the next k steps, none if
+    /// a bad state is reachable from r
+    fn image(&mut self, r: Edge, k: usize, budget: &Budget) -> Option<Option<Edge>> {
+        let mut solver = ItpSolver::new();
+        let mut max_var = self.uts.max_var;
+        let (roots, cnf) = self.circuit.cnf(
+            &[r],
+            |v| v.lit(),
+            || {
+                max_var += 1;
+                max_var
+            },
+        );
+        solver.add_clause(&roots, true);
+        solver.add_clause(&[Lit::constant(true)], true);
+        for c in cnf.iter() {
+            solver.add_clause(c, true);
+        }
+        for c in self.uts.ts.trans.iter() {
+            solver.add_clause(&self.uts.lits_next(c, 0), true);
+        }
+        for c in self.uts.ts.constraints.iter() {
+            solver.add_clause(&[*c], true);
+        }
+        for u in 1..=k {
+            for c in self.uts.ts.trans.iter() {
+                solver.add_clause(&self.uts.lits_next(c, u), false);
+            }
+            for c in self.uts.ts.constraints.iter() {
+                solver.add_clause(&[self.uts.lit_next(*c, u)], false);
+            }
+        }
+        let bads: Vec<Lit> = (1..=k)
+            .map(|u| self.uts.lit_next(self.uts.ts.bad, u))
+            .collect();
+        solver.add_clause(&bads, false);
+        if solver.solve(budget)? {
+            return Some(None);
+        }
+        let itp = solver.interpolant();
+        // the interpolant is over the latchs of the first step
+        let ts = &self.uts.ts;
+        let image = solver.circuit.copy(itp, &mut self.circuit, |v| {
+            if v == Var::new(0) {
+                return v.lit();
+            }
+            let l = ts.lit_prev(v.lit());
+            assert!(ts.is_latch(l.var()));
+            l
+        });
+        Some(Some(image))
+    }
+
+    /// checks whether a implies b
+    fn implies(&self, a: Edge, b: Edge) -> bool {
+        let mut solver = satif_cadical::Solver::new();
+        let mut max_var = self.uts.max_var;
+        let (roots, cnf) = self.circuit.cnf(
+            &[a, b],
+            |v| v.lit(),
+            || {
+                max_var += 1;
+                max_var
+            },
+        );
+        solver.new_var_to(max_var);
+        for c in cnf.iter() {
+            solver.add_clause(c);
+        }
+        !solver.solve(&[roots[0], !roots[1]])
+    }
+
+    /// checks the fixpoint with an independent solver, it contains the initial states, is
+    /// inductive relative to the constraints and excludes the bad states
+    fn verify(&self, r: Edge) -> bool {
+        if !self.implies(self.init, r) {
+            return false;
+        }
+        let uts = &self.uts;
+        let mut max_var = uts.max_var;
+        let mut new_var = || {
+            max_var += 1;
+            max_var
+        };
+        let (r0, cnf0) = self.circuit.cnf(&[r], |v| v.lit(), &mut new_var);
+        let (r1, cnf1) = self
+            .circuit
+            .cnf(&[r], |v| uts.lit_next(v.lit(), 1), &mut new_var);
+        let mut solver = satif_cadical::Solver::new();
+        solver.new_var_to(max_var);
+        for c in cnf0.iter().chain(cnf1.iter()) {
+            solver.add_clause(c);
+        }
+        uts.load_trans(&mut solver, 0, true);
+        !solver.solve(&[r0[0], !r1[0]]) && !solver.solve(&[r0[0], uts.ts.bad])
+    }
+
+    /// searches a counterexample within the depth by bmc
+    fn counterexample(&mut self, depth: usize) -> bool {
+        let mut solver: Box<dyn Satif> = Box::new(satif_cadical::Solver::new());
+        self.uts.ts.load_init(solver.as_mut());
+        for k in 0..=depth {
+            self.uts.load_trans(solver.as_mut(), k, true);
+            if solver.solve(&[self.uts.lit_next(self.uts.ts.bad, k)]) {
+                if self.options.verbose > 0 {
+                    println!("imc found cex in depth {k}");
+                }
+                self.cex = Some((solver, k));
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Engine for IMC {
+    fn check(&mut self, budget: &Budget) -> Option<bool> {
+        if self.counterexample(0) {
+            return Some(false);
+        }
+        // the bound grows until a fixpoint or a counterexample is found or the budget ends
+        let mut k = 1;
+        loop {
+            if budget.exhausted() || budget.depth_exceeded(k) {
+                if self.options.verbose > 0 {
+                    println!("imc stopped in bound {k}");
+                }
+                return None;
+            }
+            if self.options.verbose > 0 {
+                println!("imc bound: {k}");
+            }
+            heartbeat::beat(Progress::Depth(k));
+            self.uts.unroll_to(k + 1);
+            let mut r = self.init;
+            let mut first = true;
+            loop {
+                let Some(image) = self.image(r, k, budget)? else {
+                    if first && self.counterexample(k) {
+                        return Some(false);
+                    }
+                    break;
+                };
+                if self.implies(image, r) {
+                    if self.options.verbose > 0 {
+                        println!("imc fixpoint in bound {k}");
+                    }
+                    if !self.verify(r) {
+                        // the engine can not prove safe with a wrong fixpoint, it gives up
+                        println!("Error: imc fixpoint is not an inductive invariant");
+                        return None;
+                    }
+                    self.invariant = Some(r);
+                    return Some(true);
+                }
+                r = self.circuit.or(r, image);
+                first = false;
+            }
+            k += 1;
+        }
+    }
+
+    fn bound(&self) -> Option<usize> {
+        Some(self.uts.num_unroll.saturating_sub(1))
+    }
+
+    fn certifaiger(&mut self, aig: &Aig) -> Option<Aig> {
+        let invariant = self.invariant?;
+        let ts = &self.uts.ts;
+        let mut certifaiger = aig.clone();
+        let invariant = self.circuit.to_aig(invariant, &mut certifaiger, |v| {
+            AigEdge::from_lit(ts.restore(v.lit()))
+        });
+        let merged: Vec<AigEdge> = ts
+            .merged_clauses()
+            .iter()
+            .map(|c| {
+                let c: Vec<AigEdge> = c.iter().map(|l| AigEdge::from_lit(*l)).collect();
+                certifaiger.new_ors_node(c.into_iter())
+            })
+            .collect();
+        let merged = certifaiger.new_ands_node(merged.into_iter());
+        let invariant = certifaiger.new_and_node(invariant, merged);
+        let constrains: Vec<AigEdge> = certifaiger.constraints.iter().map(|e| !*e).collect();
+        let constrains = certifaiger.new_ors_node(constrains.into_iter());
+        let invariant = certifaiger.new_or_node(!invariant, constrains);
+        certifaiger.bads.clear();
+        certifaiger.outputs.clear();
+        certifaiger.outputs.push(invariant);
+        Some(certifaiger)
+    }
+
+    fn witness(&mut self, aig: &Aig) -> Result<String, Error> {
+        let (solver, depth) = self.cex.as_mut().ok_or(Error::Unsupported("witness"))?;
+        let mut wit = vec![self.uts.ts.restore_state(|l| solver.sat_value(l))];
+        for k in 0..=*depth {
+            let mut w = Cube::new();
+            for l in self.uts.ts.inputs.iter() {
+                let l = l.lit();
+                if let Some(v) = solver.sat_value(self.uts.lit_next(l, k)) {
+                    w.push(self.uts.ts.restore(l.not_if(!v)));
+                }
+            }
+            wit.push(w);
+        }
+        witness_encode(aig, &wit)
+    }
+}
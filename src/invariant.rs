@@ -0,0 +1,169 @@
+use aig::{Aig, AigEdge};
+use giputils::hash::GHashMap;
+use logic_form::{Clause, Lit, Var};
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Error, ErrorKind, Write},
+    path::Path,
+};
+
+/// the symbol of an aig variable, latchs and inputs without symbol are named by their kind
+pub(crate) fn symbol(aig: &Aig, var: usize) -> String {
+    if let Some(s) = aig.symbols.get(&var) {
+        return s.clone();
+    }
+    if aig.latchs.iter().any(|l| l.input == var) {
+        format!("l{var}")
+    } else if aig.inputs.contains(&var) {
+        format!("i{var}")
+    } else {
+        format!("n{var}")
+    }
+}
+
+fn dimacs_lit(l: Lit) -> String {
+    let v: usize = l.var().into();
+    if l.polarity() {
+        format!("{v}")
+    } else {
+        format!("-{v}")
+    }
+}
+
+/// writes the invariant clauses over the original aig variables, the format is chosen by
+/// the file extension: dimacs cnf for .cnf, aiger with the invariant as the only output
+/// for .aig and .aag, and readable text with latch symbols otherwise
+pub fn write_invariant(aig: &Aig, invariant: &[Clause], path: &Path) -> io::Result<()> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match ext {
+        "cnf" => {
+            let mut vars: Vec<usize> = invariant
+                .iter()
+                .flat_map(|c| c.iter())
+                .map(|l| l.var().into())
+                .collect();
+            vars.sort();
+            vars.dedup();
+            let mut file = fs::File::create(path)?;
+            for v in vars.iter() {
+                writeln!(file, "c {v} {}", symbol(aig, *v))?;
+            }
+            let max_var = vars.last().copied().unwrap_or(0);
+            writeln!(file, "p cnf {max_var} {}", invariant.len())?;
+            for c in invariant {
+                let c: Vec<String> = c.iter().map(|l| dimacs_lit(*l)).collect();
+                writeln!(file, "{} 0", c.join(" "))?;
+            }
+        }
+        "aig" | "aag" => {
+            let mut inv_aig = aig.clone();
+            let clauses: Vec<AigEdge> = invariant
+                .iter()
+                .map(|c| {
+                    let c: Vec<AigEdge> = c.iter().map(|l| AigEdge::from_lit(*l)).collect();
+                    inv_aig.new_ors_node(c.into_iter())
+                })
+                .collect();
+            let inv = inv_aig.new_ands_node(clauses.into_iter());
+            inv_aig.outputs = vec![inv];
+            inv_aig.to_file(path.to_str().unwrap(), ext == "aag");
+        }
+        _ => {
+            let mut text = String::new();
+            for c in invariant {
+                let c: Vec<String> = c
+                    .iter()
+                    .map(|l| {
+                        let s = symbol(aig, l.var().into());
+                        if l.polarity() {
+                            s
+                        } else {
+                            format!("!{s}")
+                        }
+                    })
+                    .collect();
+                writeln!(text, "{}", c.join(" | ")).unwrap();
+            }
+            fs::write(path, text)?;
+        }
+    }
+    Ok(())
+}
+
+/// reads the clauses of a dimacs cnf
+pub(crate) fn read_dimacs(content: &str) -> io::Result<Vec<Clause>> {
+    let invalid = |line: &str| Error::new(ErrorKind::InvalidData, format!("invalid lemma: {line}"));
+    let mut lemmas = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('c') || line.starts_with('p') {
+            continue;
+        }
+        let mut c = Clause::new();
+        for l in line.split_whitespace() {
+            let l: isize = l.parse().map_err(|_| invalid(line))?;
+            if l == 0 {
+                break;
+            }
+            c.push(Lit::new(Var::new(l.unsigned_abs()), l > 0));
+        }
+        lemmas.push(c);
+    }
+    Ok(lemmas)
+}
+
+/// reads clauses over the original aig variables written by write_invariant, dimacs cnf
+/// for .cnf and readable text with symbols otherwise
+pub fn read_lemmas(aig: &Aig, path: &Path) -> io::Result<Vec<Clause>> {
+    let invalid = |line: &str| Error::new(ErrorKind::InvalidData, format!("invalid lemma: {line}"));
+    let content = fs::read_to_string(path)?;
+    if path.extension().is_some_and(|e| e == "cnf") {
+        return read_dimacs(&content);
+    }
+    let mut lemmas = Vec::new();
+    let vars = aig
+        .latchs
+        .iter()
+        .map(|l| l.input)
+        .chain(aig.inputs.iter().copied());
+    let names: GHashMap<String, usize> = vars.map(|v| (symbol(aig, v), v)).collect();
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let mut c = Clause::new();
+        for l in line.split('|').map(str::trim) {
+            let (name, polarity) = match l.strip_prefix('!') {
+                Some(name) => (name, false),
+                None => (l, true),
+            };
+            let v = names.get(name).ok_or_else(|| invalid(line))?;
+            c.push(Lit::new(Var::new(*v), polarity));
+        }
+        lemmas.push(c);
+    }
+    Ok(lemmas)
+}
+
+/// maps lemmas over the variables of one aig to another by latch symbol, lemmas over latches
+/// without a symbol in both aigs are dropped
+pub fn map_lemmas_by_symbol(from: &Aig, to: &Aig, lemmas: &[Clause]) -> Vec<Clause> {
+    let names: GHashMap<&String, usize> = to
+        .latchs
+        .iter()
+        .filter_map(|l| to.symbols.get(&l.input).map(|s| (s, l.input)))
+        .collect();
+    let map: GHashMap<Var, Var> = from
+        .latchs
+        .iter()
+        .filter_map(|l| {
+            let v = names.get(from.symbols.get(&l.input)?)?;
+            Some((Var::new(l.input), Var::new(*v)))
+        })
+        .collect();
+    lemmas
+        .iter()
+        .filter_map(|c| {
+            c.iter()
+                .map(|l| map.get(&l.var()).map(|v| Lit::new(*v, l.polarity())))
+                .collect()
+        })
+        .collect()
+}
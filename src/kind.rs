@@ -1,16 +1,24 @@
 use crate::{
+    budget::Budget,
     options::Options,
+    portfolio::{
+        heartbeat::{self, Progress},
+        share::ShareWorker,
+    },
+    report::{Report, SolveStatistic},
     transys::{unroll::TransysUnroll, Transys},
-    witness_encode, Engine,
+    witness_encode, Engine, Error,
 };
 use aig::{Aig, AigEdge};
-use logic_form::Cube;
+use logic_form::{Cube, Lit};
 use satif::Satif;
 
 pub struct Kind {
     uts: TransysUnroll,
     options: Options,
     solver: Box<dyn Satif>,
+    share: Option<ShareWorker>,
+    statistic: SolveStatistic,
 }
 
 impl Kind {
@@ -29,9 +37,15 @@ impl Kind {
             uts,
             options,
             solver,
+            share: None,
+            statistic: SolveStatistic::default(),
         }
     }
 
+    pub fn set_share(&mut self, share: ShareWorker) {
+        self.share = Some(share);
+    }
+
     // pub fn check_in_depth(&mut self, depth: usize) -> bool {
     //     println!("{}", self.options.model);
     //     assert!(depth > 0);
@@ -55,6 +69,16 @@ impl Kind {
     //     false
     // }
 
+    /// solves within the remaining time of the budget, none if it runs out
+    fn solve(&mut self, assump: &[Lit], budget: &Budget) -> Option<bool> {
+        match budget.remaining() {
+            Some(limit) => self
+                .statistic
+                .solve(|| self.solver.solve_with_limit(assump, limit)),
+            None => Some(self.statistic.solve(|| self.solver.solve(assump))),
+        }
+    }
+
     pub fn reset_solver(&mut self) {
         self.solver = if self.options.kind.kind_kissat {
             Box::new(satif_kissat::Solver::new())
@@ -65,10 +89,16 @@ impl Kind {
 }
 
 impl Engine for Kind {
-    fn check(&mut self) -> Option<bool> {
+    fn check(&mut self, budget: &Budget) -> Option<bool> {
         let step = self.options.step as usize;
         self.uts.load_trans(self.solver.as_mut(), 0, true);
         for k in (step..).step_by(step) {
+            if budget.exhausted() || budget.depth_exceeded(k) {
+                if self.options.verbose > 0 {
+                    println!("k-induction stopped in depth {k}");
+                }
+                return None;
+            }
             let bmc_k = k - 1;
             let start = k + 1 - step;
             if self.options.kind.kind_kissat {
@@ -87,18 +117,33 @@ impl Engine for Kind {
             for i in start..=bmc_k {
                 self.uts.load_trans(self.solver.as_mut(), i, true);
             }
-            if !self.options.kind.no_bmc {
+            let safe = self
+                .share
+                .as_ref()
+                .is_some_and(|s| s.safe_depth().is_some_and(|d| d >= bmc_k));
+            if !self.options.kind.no_bmc && !safe {
                 let mut assump = self.uts.ts.init.clone();
                 assump.extend_from_slice(&self.uts.lits_next(&self.uts.ts.bad.cube(), bmc_k));
                 if self.options.verbose > 0 {
                     println!("kind bmc depth: {bmc_k}");
                 }
-                if self.solver.solve(&assump) {
+                let Some(res) = self.solve(&assump, budget) else {
+                    if self.options.verbose > 0 {
+                        println!("k-induction stopped in bmc depth {bmc_k}");
+                    }
+                    return None;
+                };
+                if res {
                     if self.options.verbose > 0 {
                         println!("bmc found cex in depth {bmc_k}");
                     }
                     return Some(false);
                 }
+                if let Some(share) = &self.share {
+                    if step == 1 {
+                        share.publish_safe_depth(bmc_k);
+                    }
+                }
             }
             for i in bmc_k + 1 - step..=bmc_k {
                 self.solver
@@ -109,14 +154,21 @@ impl Engine for Kind {
             if self.options.verbose > 0 {
                 println!("kind depth: {k}");
             }
+            heartbeat::beat(Progress::Depth(k));
             let res = if self.options.kind.kind_kissat {
                 for l in self.uts.lits_next(&self.uts.ts.bad.cube(), k) {
                     self.solver.add_clause(&[l]);
                 }
-                self.solver.solve(&[])
+                self.solve(&[], budget)
             } else {
-                self.solver
-                    .solve(&self.uts.lits_next(&self.uts.ts.bad.cube(), k))
+                let assump = self.uts.lits_next(&self.uts.ts.bad.cube(), k);
+                self.solve(&assump, budget)
+            };
+            let Some(res) = res else {
+                if self.options.verbose > 0 {
+                    println!("k-induction stopped in depth {k}");
+                }
+                return None;
             };
             if !res {
                 println!("k-induction proofed in depth {k}");
@@ -126,10 +178,18 @@ impl Engine for Kind {
         unreachable!();
     }
 
-    fn certifaiger(&mut self, aig: &Aig) -> Aig {
+    fn bound(&self) -> Option<usize> {
+        Some(self.uts.num_unroll)
+    }
+
+    fn certifaiger(&mut self, aig: &Aig) -> Option<Aig> {
         if self.options.kind.simple_path {
             //TODO: support certifaiger with simple path constraint
-            panic!("k-induction with simple path constraint not support certifaiger");
+            return None;
+        }
+        if self.uts.ts.has_merged() {
+            // the k-induction does not hold on the aig without the merged latchs
+            return None;
         }
         let mut certifaiger = aig.clone();
         let ni = aig.inputs.len();
@@ -225,17 +285,11 @@ impl Engine for Kind {
         certifaiger.outputs.clear();
         certifaiger.outputs.push(bads);
         assert!(certifaiger.inputs.len() + certifaiger.latchs.len() == sum + k);
-        certifaiger
+        Some(certifaiger)
     }
 
-    fn witness(&mut self, aig: &Aig) -> String {
-        let mut wit = vec![Cube::new()];
-        for l in self.uts.ts.latchs.iter() {
-            let l = l.lit();
-            if let Some(v) = self.solver.sat_value(l) {
-                wit[0].push(self.uts.ts.restore(l.not_if(!v)));
-            }
-        }
+    fn witness(&mut self, aig: &Aig) -> Result<String, Error> {
+        let mut wit = vec![self.uts.ts.restore_state(|l| self.solver.sat_value(l))];
         for k in 0..=self.uts.num_unroll {
             let mut w = Cube::new();
             for l in self.uts.ts.inputs.iter() {
@@ -249,4 +303,14 @@ impl Engine for Kind {
         }
         witness_encode(aig, &wit)
     }
+
+    fn statistic(&mut self) {
+        println!("{:#?}", self.statistic);
+    }
+
+    fn report(&mut self, report: &mut Report) {
+        report
+            .statistic
+            .insert("kind".to_string(), self.statistic.json());
+    }
 }
@@ -1,135 +1,600 @@
 #![allow(non_snake_case)]
 #![feature(get_mut_unchecked)]
 
+pub mod abs;
 pub mod bmc;
+pub mod budget;
+pub mod certify;
 pub mod frontend;
 mod gipsat;
 pub mod ic3;
+pub mod imc;
+pub mod invariant;
 pub mod kind;
+pub mod multiprop;
 pub mod options;
 pub mod portfolio;
+pub mod report;
 pub mod transys;
+pub mod vcd;
 
-use aig::{Aig, TernarySimulate};
+use abs::Localization;
+use aig::{Aig, AigEdge, TernarySimulate};
+use bmc::BMC;
+use budget::Budget;
+pub use certify::check_witness;
+use frontend::{
+    aig::{aig_preprocess, liveness_to_safety, ternary_constant_latchs},
+    btor::Btor,
+};
 use giputils::hash::GHashMap;
-use logic_form::{Cube, Lbool, Var};
+use ic3::IC3;
+use imc::IMC;
+use kind::Kind;
+use logic_form::{Clause, Cube, Lbool, Var};
 use options::Options;
+use portfolio::{Portfolio, ThreadPortfolio};
+use report::Report;
 use std::{
+    fmt::{self, Display},
     fs::File,
     io::{self, Write},
+    path::Path,
     process::Command,
 };
+use transys::{builder::TransysBuilder, Transys};
+use vcd::write_vcd;
 
 pub trait Engine {
-    fn check(&mut self) -> Option<bool>;
+    /// returns none if the result is unknown, e.g. when the budget is exhausted
+    fn check(&mut self, budget: &Budget) -> Option<bool>;
+
+    /// the depth or frame reached so far
+    fn bound(&self) -> Option<usize> {
+        None
+    }
 
-    fn certifaiger(&mut self, _aig: &Aig) -> Aig {
-        panic!("unsupport certifaiger");
+    fn certifaiger(&mut self, _aig: &Aig) -> Option<Aig> {
+        None
     }
 
-    fn witness(&mut self, _aig: &Aig) -> String {
-        panic!("unsupport witness");
+    /// the aiger witness of an unsafe result
+    fn witness(&mut self, _aig: &Aig) -> Result<String, Error> {
+        Err(Error::Unsupported("witness"))
+    }
+
+    /// clauses of the inductive invariant over the original aig variables
+    fn invariant(&mut self) -> Option<Vec<Clause>> {
+        None
     }
 
     fn statistic(&mut self) {}
+
+    /// fills the engine specific part of the report after check
+    fn report(&mut self, _report: &mut Report) {}
 }
 
-pub fn witness_encode(aig: &Aig, witness: &[Cube]) -> String {
-    let mut wit = vec!["1".to_string(), "b".to_string()];
-    let map: GHashMap<Var, bool> =
-        GHashMap::from_iter(witness[0].iter().map(|l| (l.var(), l.polarity())));
-    let mut line = String::new();
-    let mut state = Vec::new();
-    for l in aig.latchs.iter() {
-        let r = if let Some(r) = l.init {
-            r
-        } else if let Some(r) = map.get(&Var::new(l.input)) {
-            *r
-        } else {
-            true
-        };
-        state.push(Lbool::from(r));
-        line.push(if r { '1' } else { '0' })
-    }
-    wit.push(line);
-    let mut simulate = TernarySimulate::new(aig, state);
-    for c in witness[1..].iter() {
-        let map: GHashMap<Var, bool> =
-            GHashMap::from_iter(c.iter().map(|l| (l.var(), l.polarity())));
-        let mut line = String::new();
-        let mut input = Vec::new();
-        for l in aig.inputs.iter() {
-            let r = if let Some(r) = map.get(&Var::new(*l)) {
-                *r
+#[derive(Debug)]
+pub enum Error {
+    /// the engine or configuration does not support the requested feature
+    Unsupported(&'static str),
+    /// the certificate or witness is rejected, with the reason
+    CertifyFailed(String),
+    /// the model, portfolio config or replay file is malformed, with the reason
+    InvalidInput(String),
+    Io(io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Unsupported(s) => write!(f, "unsupport {s}"),
+            Error::CertifyFailed(s) => write!(f, "certify failed: {s}"),
+            Error::InvalidInput(s) => write!(f, "{s}"),
+            Error::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+pub enum Verdict {
+    /// safe, with the certifaiger certificate if requested
+    Safe(Option<Aig>),
+    /// unsafe, with the aiger witness if requested
+    Unsafe(Option<String>),
+    /// unknown, with the depth or frame reached by the engine
+    Unknown(Option<usize>),
+}
+
+pub fn transys(options: &Options, aig: &Aig) -> Result<Transys, Error> {
+    let (aig, restore, retimed) = aig_preprocess(aig, options)?;
+    let mut ts = TransysBuilder::from_aig(&aig, &restore).build();
+    ts.retimed = retimed;
+    let is_ic3 = matches!(options.engine, options::Engine::IC3 | options::Engine::Abs);
+    if is_ic3 && options.preprocess.const_latch {
+        let removed = ts.remove_constant_latchs(&ternary_constant_latchs(&aig));
+        if options.verbose > 0 {
+            println!("ternary simulation: {removed} constant latchs removed");
+        }
+    }
+    if options.preprocess.scorr {
+        // a depth 1 correspondence keeps the ic3 invariant inductive with the merged latchs,
+        // as required by certificates and exported invariants
+        let depth =
+            if options.certificate.is_some() || options.certify || options.invariant.is_some() {
+                1
             } else {
-                true
+                options.preprocess.scorr_depth
             };
-            line.push(if r { '1' } else { '0' });
-            input.push(Lbool::from(r));
+        let merged = ts.signal_correspondence(depth);
+        if options.verbose > 0 {
+            println!("scorr: {merged} latchs merged");
         }
-        wit.push(line);
-        simulate.simulate(input);
     }
-    let p = aig
+    let assert_constrain = is_ic3;
+    let keep_dep = assert_constrain;
+    // below is to use minisat::SimpSolver to simplify the clauses
+    let ts = ts.simplify(&[], keep_dep, !assert_constrain);
+    if options.verbose > 1 {
+        ts.print_info();
+    }
+    Ok(ts)
+}
+
+/// creates the engine selected in options, the aig should have a single bad property
+/// unless the portfolio engine is selected
+pub fn new_engine(options: &Options, aig: &Aig) -> Result<Box<dyn Engine>, Error> {
+    if options.invariant.is_some()
+        && !matches!(
+            options.engine,
+            options::Engine::IC3 | options::Engine::Abs | options::Engine::Portfolio
+        )
+    {
+        return Err(Error::Unsupported("invariant export of this engine"));
+    }
+    if let options::Engine::Portfolio = options.engine {
+        if options.portfolio.thread {
+            return Ok(Box::new(ThreadPortfolio::new(options.clone(), aig)?));
+        }
+        return Ok(Box::new(Portfolio::new(options.clone(), aig)?));
+    }
+    let ts = transys(options, aig)?;
+    let engine: Box<dyn Engine> = match options.engine {
+        options::Engine::IC3 => {
+            let mut lemmas = pre_lemmas(options, aig, &ts)?;
+            if options.preprocess.sec.is_some() {
+                lemmas.extend(sec_lemmas(options, &ts));
+            }
+            Box::new(IC3::new(options.clone(), ts, lemmas))
+        }
+        options::Engine::Kind => Box::new(Kind::new(options.clone(), ts)),
+        options::Engine::BMC => Box::new(BMC::new(options.clone(), ts)),
+        options::Engine::Abs => Box::new(Localization::new(options.clone(), ts)),
+        options::Engine::IMC => Box::new(IMC::new(options.clone(), ts)),
+        options::Engine::Portfolio => unreachable!(),
+    };
+    Ok(engine)
+}
+
+/// the lemmas given by --lemmas mapped to the transition system, through the latch symbols
+/// if they were written for --prev-model. only the subset that is inductive relative to
+/// the transition system is kept
+pub fn pre_lemmas(options: &Options, aig: &Aig, ts: &Transys) -> Result<Vec<Clause>, Error> {
+    let Some(path) = &options.lemmas else {
+        return Ok(vec![]);
+    };
+    let lemmas = match &options.prev_model {
+        Some(prev) => {
+            let prev = Aig::from_file(path_str(prev)?);
+            let lemmas = invariant::read_lemmas(&prev, path)?;
+            invariant::map_lemmas_by_symbol(&prev, aig, &lemmas)
+        }
+        None => invariant::read_lemmas(aig, path)?,
+    };
+    let num_read = lemmas.len();
+    let lemmas = ts.inductive_subset(ts.lemmas_from_origin(&lemmas));
+    if options.verbose > 0 {
+        println!("imported {} of {num_read} lemmas", lemmas.len());
+    }
+    Ok(lemmas)
+}
+
+/// latch correspondences of a sec miter found by simulation and proved by induction
+pub fn sec_lemmas(options: &Options, ts: &Transys) -> Vec<Clause> {
+    let proved = ts.latch_correspondences(1);
+    if options.verbose > 0 {
+        println!("sec: {} latch correspondences proved", proved.len());
+    }
+    proved.iter().flat_map(|c| c.clauses()).collect()
+}
+
+/// checks the model without printing results, installing signal handlers or exiting the
+/// process, the certificate or witness is produced if options request one
+pub fn check(options: &Options, aig: &Aig, budget: &Budget) -> Result<Verdict, Error> {
+    if aig.bads.is_empty() && aig.justice.is_empty() {
+        return Ok(Verdict::Safe(None));
+    }
+    if !aig.justice.is_empty() && (options.certificate.is_some() || options.certify) {
+        // the certificate of the liveness-to-safety model does not certify the justice
+        return Err(Error::Unsupported("justice certificate"));
+    }
+    let mut engine = if let options::Engine::Portfolio = options.engine {
+        new_engine(options, aig)?
+    } else {
+        let mut model = aig.clone();
+        if !model.justice.is_empty() {
+            model = liveness_to_safety(&model);
+        }
+        if model.bads.len() > 1 {
+            model.compress_property();
+        }
+        new_engine(options, &model)?
+    };
+    // --witness only asks for the witness of an unsafe result
+    let cert = options.certificate.is_some() || options.certify;
+    let wit = cert || options.witness;
+    Ok(match engine.check(budget) {
+        Some(true) => Verdict::Safe(cert.then(|| certifaiger(&mut engine, aig)).transpose()?),
+        Some(false) => Verdict::Unsafe(
+            wit.then(|| witness(&mut engine, aig, options))
+                .transpose()?,
+        ),
+        None => Verdict::Unknown(engine.bound()),
+    })
+}
+
+/// the witness of an unsafe result, a witness of a retimed model starts from the original
+/// initial state without the retimed latchs and is replayed on the aig to make sure the
+/// translation holds
+fn witness(engine: &mut Box<dyn Engine>, aig: &Aig, options: &Options) -> Result<String, Error> {
+    let witness = engine.witness(aig)?;
+    if options.preprocess.retime {
+        check_witness(aig, &witness).map_err(|e| Error::CertifyFailed(e.to_string()))?;
+    }
+    Ok(witness)
+}
+
+/// the path as str, aiger files are read and written by utf-8 paths
+pub(crate) fn path_str(path: &Path) -> io::Result<&str> {
+    path.to_str().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("non utf-8 path {}", path.display()),
+        )
+    })
+}
+
+/// the initial latch values and the input values of each step of a witness, variables
+/// missing in the witness are true
+fn witness_values(aig: &Aig, witness: &[Cube]) -> (Vec<Lbool>, Vec<Vec<Lbool>>) {
+    let map: GHashMap<Var, bool> =
+        GHashMap::from_iter(witness[0].iter().map(|l| (l.var(), l.polarity())));
+    let state = aig
+        .latchs
+        .iter()
+        .map(|l| {
+            let r = l.init.or(map.get(&Var::new(l.input)).copied());
+            Lbool::from(r.unwrap_or(true))
+        })
+        .collect();
+    let inputs = witness[1..]
+        .iter()
+        .map(|c| {
+            let map: GHashMap<Var, bool> =
+                GHashMap::from_iter(c.iter().map(|l| (l.var(), l.polarity())));
+            aig.inputs
+                .iter()
+                .map(|l| Lbool::from(map.get(&Var::new(*l)).copied().unwrap_or(true)))
+                .collect()
+        })
+        .collect();
+    (state, inputs)
+}
+
+/// the first step of the trace in which the bad holds by ternary simulation, the
+/// constraints must hold up to it
+fn ternary_bad_step(
+    aig: &Aig,
+    state: &[Lbool],
+    inputs: &[Vec<Lbool>],
+    bad: AigEdge,
+) -> Option<usize> {
+    let mut simulate = TernarySimulate::new(aig, state.to_vec());
+    for (k, input) in inputs.iter().enumerate() {
+        simulate.simulate(input.clone());
+        if aig
+            .constraints
+            .iter()
+            .any(|c| !simulate.value(*c).is_true())
+        {
+            return None;
+        }
+        if simulate.value(bad).is_true() {
+            return Some(k);
+        }
+    }
+    None
+}
+
+/// the latch values of the next step in a ternary simulation
+fn ternary_next_state(aig: &Aig, simulate: &TernarySimulate) -> Vec<Lbool> {
+    aig.latchs.iter().map(|l| simulate.value(l.next)).collect()
+}
+
+/// replays the trace by ternary simulation from the state of step k, the constraints must
+/// hold and the bad must hold in the last step. the simulation stops early once it meets
+/// a cached state, and on success the cached states are updated
+fn ternary_replay(
+    aig: &Aig,
+    states: &mut [Vec<Lbool>],
+    inputs: &[Vec<Lbool>],
+    bad: AigEdge,
+    k: usize,
+    state: Vec<Lbool>,
+) -> bool {
+    let mut simulate = TernarySimulate::new(aig, state.clone());
+    let mut replayed = vec![state];
+    for (j, input) in inputs.iter().enumerate().skip(k) {
+        simulate.simulate(input.clone());
+        if aig
+            .constraints
+            .iter()
+            .any(|c| !simulate.value(*c).is_true())
+        {
+            return false;
+        }
+        if j + 1 == inputs.len() {
+            if !simulate.value(bad).is_true() {
+                return false;
+            }
+            break;
+        }
+        let next = ternary_next_state(aig, &simulate);
+        if next == states[j + 1] {
+            break;
+        }
+        replayed.push(next);
+    }
+    for (i, s) in replayed.into_iter().enumerate() {
+        states[k + i] = s;
+    }
+    true
+}
+
+/// shortens the trace to the first step in which a bad holds, then the inputs and the
+/// uninitialized latchs that are not needed to reach it become don't care
+fn witness_minimize(aig: &Aig, state: &mut [Lbool], inputs: &mut Vec<Vec<Lbool>>) {
+    let Some((bad, k)) = aig
         .bads
         .iter()
-        .position(|b| simulate.value(*b).is_true())
-        .unwrap();
-    wit[1] = format!("b{p}");
+        .filter_map(|b| ternary_bad_step(aig, state, inputs, *b).map(|k| (*b, k)))
+        .min_by_key(|(_, k)| *k)
+    else {
+        // witness of a justice property
+        return;
+    };
+    inputs.truncate(k + 1);
+    // the latch values before each step, a changed value is replayed from its step only
+    let mut states = vec![state.to_vec()];
+    let mut simulate = TernarySimulate::new(aig, state.to_vec());
+    for input in inputs[..k].iter() {
+        simulate.simulate(input.clone());
+        states.push(ternary_next_state(aig, &simulate));
+    }
+    for k in (0..inputs.len()).rev() {
+        for i in 0..inputs[k].len() {
+            if inputs[k][i] == Lbool::NONE {
+                continue;
+            }
+            let v = std::mem::replace(&mut inputs[k][i], Lbool::NONE);
+            let s = states[k].clone();
+            if !ternary_replay(aig, &mut states, inputs, bad, k, s) {
+                inputs[k][i] = v;
+            }
+        }
+    }
+    for (i, l) in aig.latchs.iter().enumerate() {
+        if l.init.is_none() && state[i] != Lbool::NONE {
+            let mut s = states[0].clone();
+            s[i] = Lbool::NONE;
+            if ternary_replay(aig, &mut states, inputs, bad, 0, s) {
+                state[i] = Lbool::NONE;
+            }
+        }
+    }
+}
+
+fn lbool_char(v: Lbool) -> char {
+    if v.is_true() {
+        '1'
+    } else if v == Lbool::NONE {
+        'x'
+    } else {
+        '0'
+    }
+}
+
+pub fn witness_encode(aig: &Aig, witness: &[Cube]) -> Result<String, Error> {
+    let (state, inputs) = witness_values(aig, witness);
+    encode_values(aig, state, inputs)
+}
+
+/// encodes the witness minimized to the shortest trace with don't care values
+pub fn witness_encode_minimized(aig: &Aig, witness: &[Cube]) -> Result<String, Error> {
+    let (mut state, mut inputs) = witness_values(aig, witness);
+    witness_minimize(aig, &mut state, &mut inputs);
+    encode_values(aig, state, inputs)
+}
+
+fn encode_values(aig: &Aig, state: Vec<Lbool>, inputs: Vec<Vec<Lbool>>) -> Result<String, Error> {
+    let mut wit = vec!["1".to_string(), "b".to_string()];
+    wit.push(state.iter().map(|v| lbool_char(*v)).collect());
+    let mut simulate = TernarySimulate::new(aig, state);
+    let signals: Vec<AigEdge> = aig
+        .justice
+        .iter()
+        .flatten()
+        .chain(aig.fairness.iter())
+        .copied()
+        .collect();
+    let mut trace = Vec::new();
+    for input in inputs {
+        wit.push(input.iter().map(|v| lbool_char(*v)).collect());
+        simulate.simulate(input);
+        let state: Vec<Lbool> = aig
+            .latchs
+            .iter()
+            .map(|l| simulate.value(AigEdge::from(l.input)))
+            .collect();
+        let signal: Vec<bool> = signals
+            .iter()
+            .map(|s| simulate.value(*s).is_true())
+            .collect();
+        trace.push((state, signal));
+    }
+    if let Some(p) = aig.bads.iter().position(|b| simulate.value(*b).is_true()) {
+        wit[1] = format!("b{p}");
+    } else {
+        // lasso witness of a justice property: the last state closes a loop in which
+        // all justice and fairness signals hold at least once
+        // an empty trace closes no loop
+        let k = trace.len().saturating_sub(1);
+        let num_justice: usize = aig.justice.iter().map(|j| j.len()).sum();
+        let mut begin = 0;
+        let mut prop = None;
+        for (p, j) in aig.justice.iter().enumerate() {
+            let s: Vec<usize> = (begin..begin + j.len())
+                .chain(num_justice..signals.len())
+                .collect();
+            begin += j.len();
+            if (0..k).any(|t| {
+                trace[t].0 == trace[k].0 && s.iter().all(|s| trace[t..k].iter().any(|(_, v)| v[*s]))
+            }) {
+                prop = Some(p);
+                break;
+            }
+        }
+        let p = prop.ok_or_else(|| Error::CertifyFailed("witness violates no property".into()))?;
+        // the last step only evaluates the closed loop
+        wit.pop();
+        wit[1] = format!("j{p}");
+    }
     wit.push(".\n".to_string());
-    wit.join("\n")
+    Ok(wit.join("\n"))
 }
 
-pub fn certificate(engine: &mut Box<dyn Engine>, aig: &Aig, option: &Options, res: bool) {
+fn certifaiger(engine: &mut Box<dyn Engine>, aig: &Aig) -> Result<Aig, Error> {
+    let mut certifaiger = engine
+        .certifaiger(aig)
+        .ok_or(Error::Unsupported("certifaiger"))?;
+    certifaiger = certifaiger.reencode();
+    certifaiger.symbols.clear();
+    for i in 0..aig.inputs.len() {
+        certifaiger.set_symbol(certifaiger.inputs[i], &format!("= {}", aig.inputs[i] * 2));
+    }
+    for i in 0..aig.latchs.len() {
+        certifaiger.set_symbol(
+            certifaiger.latchs[i].input,
+            &format!("= {}", aig.latchs[i].input * 2),
+        );
+    }
+    Ok(certifaiger)
+}
+
+pub fn certificate(
+    engine: &mut Box<dyn Engine>,
+    aig: &Aig,
+    option: &Options,
+    res: bool,
+    btor: Option<&Btor>,
+) -> Result<(), Error> {
+    if btor.is_some() && option.certify && option.certify_docker {
+        // certifaiger reads the model file, which is not in aiger format
+        return Err(Error::Unsupported("certifaiger check of btor2 models"));
+    }
     if res {
         if option.certificate.is_none() && !option.certify {
-            return;
-        }
-        let mut certifaiger = engine.certifaiger(aig);
-        certifaiger = certifaiger.reencode();
-        certifaiger.symbols.clear();
-        for i in 0..aig.inputs.len() {
-            certifaiger.set_symbol(certifaiger.inputs[i], &format!("= {}", aig.inputs[i] * 2));
-        }
-        for i in 0..aig.latchs.len() {
-            certifaiger.set_symbol(
-                certifaiger.latchs[i].input,
-                &format!("= {}", aig.latchs[i].input * 2),
-            );
+            return Ok(());
         }
+        let certifaiger = certifaiger(engine, aig)?;
         if let Some(certificate_path) = &option.certificate {
-            certifaiger.to_file(certificate_path.to_str().unwrap(), true);
+            certifaiger.to_file(path_str(certificate_path)?, true);
         }
         if !option.certify {
-            return;
+            return Ok(());
+        }
+        if !option.certify_docker {
+            certify::check_certificate(aig, &certifaiger)?;
+            println!("certificate check passed");
+            return Ok(());
         }
-        let certificate_file = tempfile::NamedTempFile::new().unwrap();
-        let certificate_path = certificate_file.path().as_os_str().to_str().unwrap();
+        let certificate_file = tempfile::NamedTempFile::new()?;
+        let certificate_path = path_str(certificate_file.path())?;
         certifaiger.to_file(certificate_path, true);
-        certifaiger_check(option, certificate_path);
+        certifaiger_check(option, certificate_path)
     } else {
-        if option.certificate.is_none() && !option.certify && !option.witness {
-            return;
+        if option.certificate.is_none()
+            && !option.certify
+            && !option.witness
+            && option.vcd.is_none()
+        {
+            return Ok(());
+        }
+        let mut witness = witness(engine, aig, option)?;
+        if let Some(vcd) = &option.vcd {
+            write_vcd(aig, &option.model, &witness, vcd)?;
+        }
+        if option.certify && !option.certify_docker {
+            // the aiger witness is checked before it is converted to btor2
+            check_witness(aig, &witness).map_err(|e| Error::CertifyFailed(e.to_string()))?;
+            println!("witness check passed");
+        }
+        if let Some(btor) = btor {
+            witness = btor
+                .witness_encode(&witness)
+                .map_err(|e| Error::CertifyFailed(e.to_string()))?;
         }
-        let witness = engine.witness(aig);
         if option.witness {
             println!("{}", witness);
         }
         if let Some(certificate_path) = &option.certificate {
-            let mut file: File = File::create(certificate_path).unwrap();
-            file.write_all(witness.as_bytes()).unwrap();
+            let mut file: File = File::create(certificate_path)?;
+            file.write_all(witness.as_bytes())?;
         }
-        if !option.certify {
-            return;
+        if !option.certify || !option.certify_docker {
+            return Ok(());
         }
-        let mut wit_file = tempfile::NamedTempFile::new().unwrap();
-        wit_file.write_all(witness.as_bytes()).unwrap();
-        let wit_path = wit_file.path().as_os_str().to_str().unwrap();
-        certifaiger_check(option, wit_path);
+        let mut wit_file = tempfile::NamedTempFile::new()?;
+        wit_file.write_all(witness.as_bytes())?;
+        let wit_path = path_str(wit_file.path())?;
+        certifaiger_check(option, wit_path)
     }
 }
 
-fn certifaiger_check(option: &Options, certificate: &str) {
+/// writes the invariant of a safe result to the path given by --invariant
+pub fn export_invariant(
+    engine: &mut Box<dyn Engine>,
+    aig: &Aig,
+    option: &Options,
+) -> Result<(), Error> {
+    let Some(path) = &option.invariant else {
+        return Ok(());
+    };
+    let invariant = engine.invariant().ok_or(Error::Unsupported(
+        "invariant over variables without origin",
+    ))?;
+    invariant::write_invariant(aig, &invariant, path)?;
+    Ok(())
+}
+
+pub fn certifaiger_check(option: &Options, certificate: &str) -> Result<(), Error> {
     let output = Command::new("docker")
         .args([
             "run",
@@ -147,13 +612,19 @@ fn certifaiger_check(option: &Options, certificate: &str) {
         .arg(&option.model)
         .arg(certificate)
         .output()
-        .expect("certifaiger not found, please build docker image from https://github.com/Froleyks/certifaiger");
+        .map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                "certifaiger not found, please build docker image from https://github.com/Froleyks/certifaiger",
+            )
+        })?;
     if option.verbose > 1 {
-        io::stdout().write_all(&output.stdout).unwrap();
+        io::stdout().write_all(&output.stdout)?;
     }
     if output.status.success() {
         println!("certifaiger check passed");
+        Ok(())
     } else {
-        panic!("certifaiger check failed");
+        Err(Error::CertifyFailed("rejected by certifaiger".to_string()))
     }
 }
@@ -1,43 +1,102 @@
-#![feature(ptr_metadata)]
-
 use aig::Aig;
 use clap::Parser;
 use rIC3::{
-    bmc::BMC,
-    certificate,
-    frontend::aig::aig_preprocess,
-    ic3::IC3,
-    kind::Kind,
-    options::{self, Options},
-    portfolio::Portfolio,
-    transys::builder::TransysBuilder,
+    budget::Budget,
+    certificate, check_witness, export_invariant,
+    frontend::{
+        aig::{liveness_to_safety, miter, outputs_to_bads},
+        btor::Btor,
+    },
+    multiprop::MultiProp,
+    new_engine,
+    options::{self, CheckWitnessOptions, Cli, Command, Options},
+    portfolio::{replay::Replay, Portfolio, ThreadPortfolio},
+    report::Report,
     Engine,
 };
-use std::{
-    fs,
-    mem::{self, transmute},
-    process::exit,
-    ptr,
-};
+use std::{env, fs, mem, path::PathBuf, process::exit};
+
+fn report_path(options: &Options) -> Option<PathBuf> {
+    let report = options.report.as_ref()?;
+    if report[0] != "json" {
+        panic!(
+            "Error: unsupported report format {}, only json is supported.",
+            report[0]
+        );
+    }
+    Some(PathBuf::from(&report[1]))
+}
+
+fn write_report(report: &Report, path: &Option<PathBuf>) {
+    if let Some(path) = path {
+        report
+            .write(path)
+            .unwrap_or_else(|e| panic!("Error: failed to write report: {e}"));
+    }
+}
+
+fn check_witness_main(options: CheckWitnessOptions) {
+    let mut aig = Aig::from_file(options.model.to_str().unwrap());
+    // the same properties as checked by rIC3
+    outputs_to_bads(&mut aig);
+    let witness = fs::read_to_string(&options.witness)
+        .unwrap_or_else(|e| panic!("Error: failed to read witness: {e}"));
+    match check_witness(&aig, &witness) {
+        Ok(()) => println!("witness check passed"),
+        Err(e) => {
+            println!("witness check failed: {e}");
+            exit(1);
+        }
+    }
+}
 
 fn main() {
     procspawn::init();
+    let cli = Cli::parse();
+    if let Some(Command::CheckWitness(options)) = cli.command {
+        return check_witness_main(options);
+    }
     fs::create_dir_all("/tmp/rIC3").unwrap();
-    let mut options = Options::parse();
+    let mut options = cli.options.unwrap();
     options.model = options.model.canonicalize().unwrap();
+    if let Some(replay) = &options.portfolio.replay {
+        options = Replay::load(replay)
+            .and_then(|r| r.options(&options))
+            .unwrap_or_else(|e| panic!("Error: {e}"));
+        if options.verbose > 0 {
+            println!("replay: {:?}", options.engine);
+        }
+    }
+    let report_path = report_path(&options);
+    let mut report = Report::new(&options);
+    report.config = Some(env::args().skip(1).collect::<Vec<_>>().join(" "));
     if options.verbose > 0 {
         println!("the model to be checked: {}", options.model.display());
     }
-    let mut aig = if options.model.ends_with(".btor") || options.model.ends_with(".btor2") {
-        panic!("rIC3 currently does not support parsing BTOR2 files. Please use btor2aiger (https://github.com/hwmcc/btor2tools) to first convert them to AIG format.")
+    let is_btor = options
+        .model
+        .extension()
+        .is_some_and(|e| e == "btor" || e == "btor2");
+    let btor = is_btor.then(|| {
+        Btor::from_file(options.model.to_str().unwrap()).unwrap_or_else(|e| panic!("Error: {e}"))
+    });
+    let mut aig = if let Some(btor) = &btor {
+        btor.aig.clone()
     } else {
         Aig::from_file(options.model.to_str().unwrap())
     };
+    if let Some(sec) = &options.preprocess.sec {
+        if btor.is_some() {
+            panic!("Error: sec is not supported for BTOR2 models.");
+        }
+        aig = miter(&aig, &Aig::from_file(sec.to_str().unwrap()));
+        if options.verbose > 0 {
+            println!("sec: checking {} output pairs", aig.bads.len());
+        }
+    }
     if !aig.outputs.is_empty() && !options.certify {
-        // not certifying, move outputs to bads
-        // Move outputs to bads if no bad properties exist
-        if aig.bads.is_empty() {
-            aig.bads = std::mem::take(&mut aig.outputs);
+        // not certifying, move outputs to bads if no bad properties exist
+        if outputs_to_bads(&mut aig) {
             println!(
                 "Warning: property not found, moved {} outputs to bad properties",
                 aig.bads.len()
@@ -47,14 +106,59 @@ fn main() {
         }
     }
 
+    let budget = Budget::new(&options);
+    let interrupt = budget.clone();
+    ctrlc::set_handler(move || {
+        if interrupt.is_cancelled() {
+            exit(124);
+        }
+        interrupt.cancel();
+    })
+    .unwrap();
+
     let origin_aig = aig.clone();
+    if !aig.justice.is_empty() {
+        if options.certify {
+            panic!("Error: certification of justice properties is not supported.");
+        }
+        if options.verbose > 0 {
+            println!(
+                "liveness-to-safety: {} justice properties translated into bad properties",
+                aig.justice.len()
+            );
+        }
+        aig = liveness_to_safety(&aig);
+    }
     if aig.bads.is_empty() {
         println!("warning: no property to be checked");
         if let Some(certificate) = &options.certificate {
             aig.to_file(certificate.to_str().unwrap(), true);
         }
+        report.verdict = Some(true);
+        write_report(&report, &report_path);
         exit(20);
     } else if aig.bads.len() > 1 {
+        if options.multi_prop {
+            let res = MultiProp::new(options.clone(), &aig, &origin_aig)
+                .check(btor.as_ref(), &budget)
+                .unwrap_or_else(|e| panic!("Error: {e}"));
+            report.verdict = if res.iter().any(|r| matches!(r, Some(false))) {
+                Some(false)
+            } else if res.iter().all(|r| matches!(r, Some(true))) {
+                Some(true)
+            } else {
+                None
+            };
+            report.properties = Some(res.clone());
+            write_report(&report, &report_path);
+            if res.iter().any(|r| matches!(r, Some(false))) {
+                exit(10)
+            } else if res.iter().all(|r| matches!(r, Some(true))) {
+                exit(20)
+            } else {
+                exit(0)
+            }
+        }
         if options.certify {
             panic!("Error: Multiple properties detected. Cannot compress properties when certification is enabled.");
         }
@@ -65,46 +169,36 @@ fn main() {
         aig.compress_property();
     }
     let mut engine: Box<dyn Engine> = if let options::Engine::Portfolio = options.engine {
-        Box::new(Portfolio::new(options.clone(), &origin_aig))
-    } else {
-        let (aig, restore) = aig_preprocess(&aig, &options);
-        let mut ts = TransysBuilder::from_aig(&aig, &restore).build();
-        let pre_lemmas = vec![];
-        if options.preprocess.sec {
-            panic!("sec not support");
+        if options.portfolio.thread {
+            Box::new(
+                ThreadPortfolio::new(options.clone(), &origin_aig)
+                    .unwrap_or_else(|e| panic!("Error: {e}")),
+            )
+        } else {
+            Box::new(
+                Portfolio::new(options.clone(), &origin_aig)
+                    .unwrap_or_else(|e| panic!("Error: {e}")),
+            )
         }
-
-        let assert_constrain = matches!(options.engine, options::Engine::IC3);
-        let keep_dep = assert_constrain;
-        // below is to use minisat::SimpSolver to simplify the clauses
-        ts = ts.simplify(&[], keep_dep, !assert_constrain);
-        if options.verbose > 1 {
-            ts.print_info();
-        }
-        let mut engine: Box<dyn Engine> = match options.engine {
-            options::Engine::IC3 => Box::new(IC3::new(options.clone(), ts, pre_lemmas)),
-            options::Engine::Kind => Box::new(Kind::new(options.clone(), ts)),
-            options::Engine::BMC => Box::new(BMC::new(options.clone(), ts)),
-            _ => unreachable!(),
-        };
-        if options.interrupt_statistic {
-            let e: (usize, usize) =
-                unsafe { transmute((engine.as_mut() as *mut dyn Engine).to_raw_parts()) };
-            let _ = ctrlc::set_handler(move || {
-                let e: *mut dyn Engine = unsafe {
-                    ptr::from_raw_parts_mut(
-                        e.0 as *mut (),
-                        transmute::<usize, std::ptr::DynMetadata<dyn rIC3::Engine>>(e.1),
-                    )
-                };
-                let e = unsafe { &mut *e };
-                e.statistic();
-                exit(124);
-            });
-        }
-        engine
+    } else {
+        new_engine(&options, &aig).unwrap_or_else(|e| panic!("{e}"))
     };
-    let res = engine.check();
+    let res = engine.check(&budget);
+    if report_path.is_some() {
+        report.verdict = res;
+        report.bound = engine.bound();
+        engine.report(&mut report);
+        write_report(&report, &report_path);
+    }
+    if budget.is_cancelled() {
+        if options.interrupt_statistic {
+            engine.statistic();
+        }
+        if let options::Engine::Portfolio = options.engine {
+            drop(engine);
+        }
+        exit(124);
+    }
     if options.verbose > 0 {
         print!("result: ");
     }
@@ -116,17 +210,23 @@ fn main() {
             if options.witness {
                 println!("0");
             }
-            certificate(&mut engine, &origin_aig, &options, true)
+            certificate(&mut engine, &origin_aig, &options, true, btor.as_ref())
+                .unwrap_or_else(|e| panic!("{e}"));
+            export_invariant(&mut engine, &origin_aig, &options).unwrap_or_else(|e| panic!("{e}"));
         }
         Some(false) => {
             if options.verbose > 0 {
                 println!("unsafe");
             }
-            certificate(&mut engine, &origin_aig, &options, false)
+            certificate(&mut engine, &origin_aig, &options, false, btor.as_ref())
+                .unwrap_or_else(|e| panic!("{e}"));
         }
         _ => {
             if options.verbose > 0 {
-                println!("unknown");
+                match engine.bound() {
+                    Some(b) => println!("unknown, bound: {b}"),
+                    None => println!("unknown"),
+                }
             }
             if options.witness {
                 println!("2");
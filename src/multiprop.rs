@@ -0,0 +1,155 @@
+use crate::{
+    abs::Localization,
+    bmc::BMC,
+    budget::Budget,
+    certificate,
+    frontend::btor::Btor,
+    ic3::IC3,
+    imc::IMC,
+    kind::Kind,
+    options::{self, Options},
+    path_str, transys, Engine, Error,
+};
+use aig::Aig;
+use logic_form::Clause;
+use std::{fs, path::PathBuf};
+
+pub struct MultiProp {
+    options: Options,
+    aig: Aig,
+    /// the aig before liveness-to-safety, witnesses are encoded on its properties
+    origin: Aig,
+    /// invariant lemmas of safe properties over the original aig variables,
+    /// they over-approximate the reachable states and hold for every property
+    lemmas: Vec<Clause>,
+}
+
+impl MultiProp {
+    pub fn new(options: Options, aig: &Aig, origin: &Aig) -> Self {
+        Self {
+            options,
+            aig: aig.clone(),
+            origin: origin.clone(),
+            lemmas: Vec::new(),
+        }
+    }
+
+    /// the original aig with the property p only, the bad properties of liveness-to-safety
+    /// follow the original bad properties, one for each justice property
+    fn origin_property(&self, p: usize) -> Aig {
+        let mut origin = self.origin.clone();
+        let num_bads = origin.bads.len();
+        if p < num_bads {
+            origin.bads = vec![origin.bads[p]];
+            origin.justice.clear();
+        } else {
+            origin.bads.clear();
+            origin.justice = vec![origin.justice[p - num_bads].clone()];
+        }
+        origin
+    }
+
+    fn check_property(
+        &mut self,
+        p: usize,
+        btor: Option<&Btor>,
+        budget: &Budget,
+    ) -> Result<Option<bool>, Error> {
+        let mut aig = self.aig.clone();
+        aig.bads = vec![self.aig.bads[p]];
+        let ts = transys(&self.options, &aig)?;
+        let mut options = self.options.clone();
+        options.certificate = self
+            .options
+            .certificate
+            .as_ref()
+            .map(|c| PathBuf::from(format!("{}.{p}", c.display())));
+        options.vcd = self
+            .options
+            .vcd
+            .as_ref()
+            .map(|c| PathBuf::from(format!("{}.{p}", c.display())));
+        // the certificate and witness are checked against the original property
+        let origin = self.origin_property(p);
+        let model_file = if options.certify {
+            fs::create_dir_all("/tmp/rIC3")?;
+            let file = tempfile::NamedTempFile::with_suffix_in(".aig", "/tmp/rIC3")?;
+            origin.to_file(path_str(file.path())?, false);
+            options.model = file.path().to_path_buf();
+            Some(file)
+        } else {
+            None
+        };
+        let (res, mut engine): (Option<bool>, Box<dyn Engine>) = match options.engine {
+            options::Engine::IC3 => {
+                let pre_lemmas = ts.lemmas_from_origin(&self.lemmas);
+                if options.verbose > 0 {
+                    println!("reuse {} lemmas from checked properties", pre_lemmas.len());
+                }
+                let mut ic3 = IC3::new(options.clone(), ts, pre_lemmas);
+                let res = ic3.check(budget);
+                if let Some(true) = res {
+                    self.lemmas.extend(ic3.lemmas());
+                }
+                (res, Box::new(ic3))
+            }
+            options::Engine::Kind => {
+                let mut kind = Kind::new(options.clone(), ts);
+                (kind.check(budget), Box::new(kind))
+            }
+            options::Engine::BMC => {
+                let mut bmc = BMC::new(options.clone(), ts);
+                (bmc.check(budget), Box::new(bmc))
+            }
+            options::Engine::Abs => {
+                let mut abs = Localization::new(options.clone(), ts);
+                (abs.check(budget), Box::new(abs))
+            }
+            options::Engine::IMC => {
+                let mut imc = IMC::new(options.clone(), ts);
+                (imc.check(budget), Box::new(imc))
+            }
+            _ => unreachable!(),
+        };
+        if res == Some(true) && p >= self.origin.bads.len() && options.certificate.is_some() {
+            // the certificate of the liveness-to-safety model does not certify the justice
+            return Err(Error::Unsupported("justice certificate"));
+        }
+        if let Some(res) = res {
+            certificate(&mut engine, &origin, &options, res, btor)?;
+        }
+        drop(model_file);
+        Ok(res)
+    }
+
+    /// checks the properties one by one, the default portfolio engine falls back to ic3
+    pub fn check(
+        &mut self,
+        btor: Option<&Btor>,
+        budget: &Budget,
+    ) -> Result<Vec<Option<bool>>, Error> {
+        if let options::Engine::Portfolio = self.options.engine {
+            if self.options.verbose > 0 {
+                println!("multi-property checking with ic3");
+            }
+            self.options.engine = options::Engine::IC3;
+        }
+        let mut res = Vec::new();
+        for p in 0..self.aig.bads.len() {
+            if self.options.verbose > 0 {
+                println!("checking property {p}");
+            }
+            res.push(self.check_property(p, btor, budget)?);
+        }
+        println!("property  result");
+        for (p, r) in res.iter().enumerate() {
+            let r = match r {
+                Some(true) => "safe",
+                Some(false) => "unsafe",
+                None => "unknown",
+            };
+            println!("{p:<9} {r}");
+        }
+        Ok(res)
+    }
+}
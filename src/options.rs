@@ -1,4 +1,4 @@
-use clap::{Args, Parser, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use shadow_rs::shadow;
 use std::path::PathBuf;
 
@@ -23,14 +23,48 @@ pub struct Options {
     /// certificate path
     pub certificate: Option<PathBuf>,
 
-    /// certify with certifaiger
+    /// certify the certificate or witness with the built-in checker
     #[arg(long, default_value_t = false)]
     pub certify: bool,
 
+    /// certify with the certifaiger docker image instead of the built-in checker
+    #[arg(long, default_value_t = false, requires = "certify")]
+    pub certify_docker: bool,
+
+    /// write the inductive invariant when model is safe, in dimacs cnf for .cnf,
+    /// aiger with the invariant as output for .aig or .aag, and text otherwise
+    #[arg(long)]
+    pub invariant: Option<PathBuf>,
+
+    /// seed ic3 with lemmas over the original latches, in the formats of --invariant except
+    /// aiger, lemmas that are not inductive together are dropped
+    #[arg(long)]
+    pub lemmas: Option<PathBuf>,
+
+    /// the model the lemmas were written for, its latches are mapped to the model by
+    /// symbol name, lemmas over unmatched latches are dropped
+    #[arg(long, requires = "lemmas")]
+    pub prev_model: Option<PathBuf>,
+
     /// print witness when model is unsafe
     #[arg(long, default_value_t = false)]
     pub witness: bool,
 
+    /// write the simulated witness as vcd waveforms when model is unsafe, buses are
+    /// grouped by `name[i]` symbols
+    #[arg(long)]
+    pub vcd: Option<PathBuf>,
+
+    /// check each property separately and report per-property results,
+    /// the certificate of property i is written to <CERTIFICATE>.i and its vcd to <VCD>.i
+    #[arg(long, default_value_t = false)]
+    pub multi_prop: bool,
+
+    /// write a machine-readable report of the result and statistics,
+    /// the only supported format is json
+    #[arg(long, num_args = 2, value_names = ["FORMAT", "PATH"])]
+    pub report: Option<Vec<String>>,
+
     #[command(flatten)]
     pub ic3: IC3Options,
 
@@ -43,6 +77,12 @@ pub struct Options {
     #[command(flatten)]
     pub preprocess: PreprocessOptions,
 
+    #[command(flatten)]
+    pub budget: BudgetOptions,
+
+    #[command(flatten)]
+    pub portfolio: PortfolioOptions,
+
     /// step length
     #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
     pub step: u32,
@@ -60,6 +100,41 @@ pub struct Options {
     pub interrupt_statistic: bool,
 }
 
+/// the command line, a model check or a subcommand
+#[derive(Parser, Debug)]
+#[command(
+    name = "rIC3",
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    after_help = "Copyright (C) 2023 - Present, Yuheng Su <gipsyh.icu@gmail.com>. All rights reserved."
+)]
+#[clap(long_version = build::CLAP_LONG_VERSION)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub options: Option<Options>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// replay an aiger witness on the model with ternary simulation
+    CheckWitness(CheckWitnessOptions),
+}
+
+/// replay an aiger witness on the model with ternary simulation
+#[derive(Args, Debug, Clone)]
+pub struct CheckWitnessOptions {
+    /// model file in aiger format
+    pub model: PathBuf,
+
+    /// witness file in aiger format
+    pub witness: PathBuf,
+}
+
 #[derive(Copy, Clone, ValueEnum, Debug)]
 pub enum Engine {
     /// ic3
@@ -68,6 +143,10 @@ pub enum Engine {
     Kind,
     /// bmc
     BMC,
+    /// localization abstraction refinement with ic3
+    Abs,
+    /// interpolation based model checking
+    IMC,
     /// portfolio
     Portfolio,
 }
@@ -111,6 +190,9 @@ pub struct BMCOptions {
     /// use kissat solver, otherwise cadical
     #[arg(long = "bmc-kissat", default_value_t = false)]
     pub bmc_kissat: bool,
+    /// minimize the witness to the shortest counterexample with don't care inputs
+    #[arg(long = "bmc-min-witness", default_value_t = false)]
+    pub min_witness: bool,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -128,13 +210,71 @@ pub struct KindOptions {
 
 #[derive(Args, Clone, Debug)]
 pub struct PreprocessOptions {
-    /// sec preprocess
-    #[arg(long = "sec", default_value_t = false)]
-    pub sec: bool,
+    /// sequential equivalence check of the model against this aig, inputs and outputs
+    /// are matched by position
+    #[arg(long = "sec")]
+    pub sec: Option<PathBuf>,
 
     /// disable abc preprocess
     #[arg(long = "no-abc", default_value_t = false)]
     pub no_abc: bool,
+
+    /// forward retiming with abc, skipped if a certificate or invariant is requested.
+    /// phase abstraction is not supported
+    #[arg(long = "retime", default_value_t = false)]
+    pub retime: bool,
+
+    /// remove the latchs found constant by ternary simulation before ic3
+    #[arg(long = "const-latch", default_value_t = false)]
+    pub const_latch: bool,
+
+    /// merge latchs proved equivalent or constant by signal correspondence
+    #[arg(long = "scorr", default_value_t = false)]
+    pub scorr: bool,
+
+    /// induction depth of signal correspondence, 1 when a certificate is requested
+    #[arg(long = "scorr-depth", default_value_t = 1)]
+    pub scorr_depth: usize,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct BudgetOptions {
+    /// wall-clock time limit in seconds, the result is unknown when it is reached
+    #[arg(long = "time-limit")]
+    pub time: Option<u64>,
+
+    /// maximum depth of bmc and k-induction
+    #[arg(long = "max-depth")]
+    pub depth: Option<usize>,
+
+    /// maximum number of ic3 frames
+    #[arg(long = "max-frame")]
+    pub frame: Option<usize>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct PortfolioOptions {
+    /// portfolio engine list in toml or json format, replacing the default list
+    #[arg(long = "portfolio-config")]
+    pub config: Option<PathBuf>,
+
+    /// number of portfolio engines, the engine list is trimmed by weight or
+    /// extended with reseeded ic3 engines
+    #[arg(long = "jobs", value_parser = clap::value_parser!(u32).range(1..))]
+    pub jobs: Option<u32>,
+
+    /// run the portfolio engines as threads sharing lemmas and safe depths,
+    /// memory limits of the engines are ignored
+    #[arg(long = "portfolio-thread", default_value_t = false)]
+    pub thread: bool,
+
+    /// write the winning portfolio configuration to a replay file
+    #[arg(long = "save-replay")]
+    pub save_replay: Option<PathBuf>,
+
+    /// run only the configuration recorded in a replay file
+    #[arg(long = "replay")]
+    pub replay: Option<PathBuf>,
 }
 
 impl Default for Options {
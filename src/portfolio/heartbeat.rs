@@ -0,0 +1,59 @@
+use std::{
+    env,
+    fmt::{self, Display},
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// set by the portfolio for its engines to enable heartbeats on stderr
+pub const ENV: &str = "RIC3_HEARTBEAT";
+const PREFIX: &str = "rIC3-heartbeat";
+
+#[derive(Clone, Copy, Debug)]
+pub enum Progress {
+    /// depth of bmc or k-induction
+    Depth(usize),
+    /// level and number of lemmas of ic3
+    Frame { level: usize, lemmas: usize },
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Progress::Depth(d) => write!(f, "depth {d}"),
+            Progress::Frame { level, lemmas } => write!(f, "frame {level}, {lemmas} lemmas"),
+        }
+    }
+}
+
+/// parses a heartbeat line written by beat
+pub fn parse(line: &str) -> Option<Progress> {
+    let line = line.strip_prefix(PREFIX)?;
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words[..] {
+        ["depth", d] => Some(Progress::Depth(d.parse().ok()?)),
+        ["frame", level, lemmas] => Some(Progress::Frame {
+            level: level.parse().ok()?,
+            lemmas: lemmas.parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+/// reports the progress to the portfolio, at most once per second
+pub fn beat(progress: Progress) {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    static LAST: Mutex<Option<Instant>> = Mutex::new(None);
+    if !*ENABLED.get_or_init(|| env::var_os(ENV).is_some()) {
+        return;
+    }
+    let mut last = LAST.lock().unwrap();
+    if last.is_some_and(|l| l.elapsed() < Duration::from_secs(1)) {
+        return;
+    }
+    *last = Some(Instant::now());
+    match progress {
+        Progress::Depth(d) => eprintln!("{PREFIX} depth {d}"),
+        Progress::Frame { level, lemmas } => eprintln!("{PREFIX} frame {level} {lemmas}"),
+    }
+}
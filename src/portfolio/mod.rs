@@ -0,0 +1,581 @@
+pub mod heartbeat;
+pub mod replay;
+pub mod share;
+mod thread;
+
+pub use thread::ThreadPortfolio;
+
+use crate::{budget::Budget, invariant, path_str, report::Report, Engine, Error, Options};
+use aig::Aig;
+use heartbeat::Progress;
+use logic_form::Clause;
+use process_control::{ChildExt, Control};
+use replay::Replay;
+use serde::Deserialize;
+use std::{
+    env::current_exe,
+    fs,
+    io::{BufRead, BufReader},
+    mem::take,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, Condvar, Mutex},
+    thread::spawn,
+    time::Duration,
+};
+use tempfile::{NamedTempFile, TempDir};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EngineConfig {
+    /// command line arguments of the engine
+    pub args: String,
+    /// memory limit in GiB
+    #[serde(default = "EngineConfig::default_memory_limit")]
+    pub memory_limit: usize,
+    /// engines with higher weights are kept first when the list is trimmed by --jobs
+    #[serde(default = "EngineConfig::default_weight")]
+    pub weight: usize,
+}
+
+impl EngineConfig {
+    fn default_memory_limit() -> usize {
+        16
+    }
+
+    /// engines of a config file without weights are equal and trimmed in list order
+    fn default_weight() -> usize {
+        1
+    }
+
+    fn is_ic3(&self) -> bool {
+        engine_of(&self.args) == Some("ic3")
+    }
+
+    /// ic3 and abs export the invariant of a safe result
+    fn exports_invariant(&self) -> bool {
+        exports_invariant(&self.args)
+    }
+
+    /// bmc never proves safe, it is kept with --invariant to find counterexamples
+    fn keeps_invariant(&self) -> bool {
+        self.exports_invariant() || engine_of(&self.args) == Some("bmc")
+    }
+
+    fn new(args: &str, weight: usize) -> Self {
+        Self {
+            args: args.to_string(),
+            memory_limit: Self::default_memory_limit(),
+            weight,
+        }
+    }
+}
+
+/// the engine selected by the command line arguments of a portfolio engine
+fn engine_of(args: &str) -> Option<&str> {
+    let args: Vec<&str> = args.split_whitespace().collect();
+    args.windows(2)
+        .find(|w| matches!(w[0], "-e" | "--engine"))
+        .map(|w| w[1])
+}
+
+fn exports_invariant(args: &str) -> bool {
+    matches!(engine_of(args), Some("ic3" | "abs"))
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct PortfolioConfig {
+    #[serde(rename = "engine")]
+    pub engines: Vec<EngineConfig>,
+}
+
+/// the default engines, the weights rank them for --jobs on a 1 to 10 scale: the plain
+/// ic3 first, then the ctg and bmc engines complementing it on deep counterexamples and
+/// k-induction, the randomized and heavier ctg variants are trimmed first
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        let engines = vec![
+            EngineConfig::new("-e ic3", 10),
+            EngineConfig::new("-e ic3 --ic3-ctp --rseed 5555", 6),
+            EngineConfig::new("-e ic3 --ic3-dynamic --rseed 55", 5),
+            EngineConfig::new("-e ic3 --ic3-ctg", 9),
+            EngineConfig::new("-e ic3 --ic3-ctg --ic3-ctg-limit 5", 7),
+            EngineConfig::new("-e ic3 --ic3-ctg --ic3-ctg-max 5 --ic3-ctg-limit 15", 4),
+            EngineConfig::new("-e ic3 --ic3-ctg --ic3-abs-cst --rseed 55", 4),
+            EngineConfig::new("-e ic3 --ic3-ctg --ic3-ctp", 5),
+            EngineConfig::new("-e ic3 --ic3-inn", 6),
+            EngineConfig::new("-e ic3 --ic3-ctg --ic3-inn", 7),
+            EngineConfig::new("-e ic3 --ic3-ctg --ic3-ctg-limit 5 --ic3-inn", 4),
+            EngineConfig::new("-e bmc --step 1", 6),
+            EngineConfig::new("-e bmc --step 10", 9),
+            EngineConfig::new("-e bmc --bmc-kissat --step 70", 5),
+            EngineConfig::new("-e bmc --bmc-kissat --step 135", 3),
+            EngineConfig::new("-e kind --step 1", 8),
+        ];
+        Self { engines }
+    }
+}
+
+impl PortfolioConfig {
+    /// reads a toml or json config, the format is chosen by the file extension, a config
+    /// without engines is rejected as the portfolio would never finish
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let config = fs::read_to_string(path)?;
+        let res = if path.extension().is_some_and(|e| e == "json") {
+            serde_json::from_str(&config).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&config).map_err(|e| e.to_string())
+        };
+        let config: Self =
+            res.map_err(|e| Error::InvalidInput(format!("invalid portfolio config: {e}")))?;
+        if config.engines.is_empty() {
+            return Err(Error::InvalidInput(
+                "invalid portfolio config: no engine".to_string(),
+            ));
+        }
+        Ok(config)
+    }
+
+    /// the engine list selected by --portfolio-config and --jobs, only engines that
+    /// export their invariant and bmc are kept if --invariant is given
+    pub fn from_options(option: &Options) -> Result<Self, Error> {
+        let mut config = match &option.portfolio.config {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        if option.invariant.is_some() {
+            config.engines.retain(|e| e.keeps_invariant());
+            if !config.engines.iter().any(|e| e.exports_invariant()) {
+                return Err(Error::Unsupported(
+                    "invariant export of the portfolio engines",
+                ));
+            }
+        }
+        if let Some(jobs) = option.portfolio.jobs {
+            config.resize(jobs as usize);
+        }
+        Ok(config)
+    }
+
+    /// trims the engines with the lowest weights, or extends the list with ic3 engines
+    /// of new random seeds in the order of their weights
+    pub fn resize(&mut self, jobs: usize) {
+        let mut order: Vec<usize> = (0..self.engines.len()).collect();
+        order.sort_by_key(|i| std::cmp::Reverse(self.engines[*i].weight));
+        if jobs <= self.engines.len() {
+            let mut keep = order[..jobs].to_vec();
+            keep.sort();
+            self.engines = keep.into_iter().map(|i| self.engines[i].clone()).collect();
+            return;
+        }
+        let ic3: Vec<EngineConfig> = order
+            .into_iter()
+            .map(|i| self.engines[i].clone())
+            .filter(|e| e.is_ic3())
+            .collect();
+        for seed in 1.. {
+            for e in ic3.iter() {
+                if self.engines.len() == jobs {
+                    return;
+                }
+                let mut args: Vec<&str> = e.args.split_whitespace().collect();
+                if let Some(p) = args.iter().position(|a| *a == "--rseed") {
+                    args.drain(p..(p + 2).min(args.len()));
+                }
+                self.engines.push(EngineConfig {
+                    args: format!("{} --rseed {seed}", args.join(" ")),
+                    ..e.clone()
+                });
+            }
+            if ic3.is_empty() {
+                return;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+enum PortfolioState {
+    #[default]
+    Checking,
+    /// result, engine index, engine arguments and certificate
+    Finished(bool, usize, Vec<String>, Option<NamedTempFile>),
+    Terminate,
+}
+
+impl PortfolioState {
+    fn is_checking(&self) -> bool {
+        matches!(self, Self::Checking)
+    }
+
+    /// the earlier engine in the list wins a tie
+    fn wins(&self, id: usize) -> bool {
+        match self {
+            Self::Checking => true,
+            Self::Finished(_, winner, _, _) => id < *winner,
+            Self::Terminate => false,
+        }
+    }
+
+    fn result(&mut self) -> (bool, usize, Vec<String>, Option<NamedTempFile>) {
+        let Self::Finished(res, id, args, certificate) = self else {
+            panic!()
+        };
+        (*res, *id, take(args), take(certificate))
+    }
+}
+
+/// the last known state of a portfolio engine
+struct EngineStatus {
+    config: String,
+    progress: Option<Progress>,
+    /// exit code, none if the engine is running or killed
+    exit: Option<i32>,
+    /// the engine process has been reaped, finished or killed
+    exited: bool,
+    /// the last line of stderr that is not a heartbeat
+    message: Option<String>,
+}
+
+pub struct Portfolio {
+    option: Options,
+    _model_file: NamedTempFile,
+    /// engine commands with their memory limits in bytes
+    engines: Vec<(Command, usize)>,
+    temp_dir: TempDir,
+    engine_pids: Arc<Mutex<Vec<i32>>>,
+    certificate: Option<NamedTempFile>,
+    /// the invariant written by the winning engine
+    invariant: Option<PathBuf>,
+    best_config: Option<String>,
+    status: Arc<Mutex<Vec<EngineStatus>>>,
+    result: Arc<(Mutex<PortfolioState>, Condvar)>,
+}
+
+impl Portfolio {
+    pub fn new(option: Options, aig: &Aig) -> Result<Self, Error> {
+        fs::create_dir_all("/tmp/rIC3/")?;
+        let temp_dir = tempfile::TempDir::new_in("/tmp/rIC3/")?;
+        let temp_dir_path = temp_dir.path();
+        let model_file = tempfile::NamedTempFile::with_suffix_in(".aig", temp_dir_path)?;
+        let model_path = path_str(model_file.path())?;
+        aig.to_file(model_path, false);
+        let exe = current_exe()?;
+        let config = PortfolioConfig::from_options(&option)?;
+        let mut engines = Vec::new();
+        let mut new_engine = |args: &str, memory_limit: usize| {
+            let args = args.split_whitespace();
+            let mut engine = Command::new(&exe);
+            engine.env("RIC3_TMP_DIR", temp_dir_path);
+            engine.env(heartbeat::ENV, "1");
+            engine.arg(model_path);
+            engine.arg("-v");
+            engine.arg("0");
+            for a in args {
+                engine.arg(a);
+            }
+            if let Some(lemmas) = &option.lemmas {
+                engine.arg("--lemmas").arg(lemmas);
+            }
+            if let Some(prev_model) = &option.prev_model {
+                engine.arg("--prev-model").arg(prev_model);
+            }
+            if let Some(depth) = option.budget.depth {
+                engine.arg("--max-depth").arg(depth.to_string());
+            }
+            if let Some(frame) = option.budget.frame {
+                engine.arg("--max-frame").arg(frame.to_string());
+            }
+            engines.push((engine, memory_limit * 1024 * 1024 * 1024));
+        };
+        for e in config.engines.iter() {
+            new_engine(&e.args, e.memory_limit);
+        }
+        Ok(Self {
+            option,
+            _model_file: model_file,
+            engines,
+            temp_dir,
+            certificate: None,
+            invariant: None,
+            best_config: None,
+            status: Default::default(),
+            engine_pids: Default::default(),
+            result: Arc::new((Mutex::new(PortfolioState::default()), Condvar::new())),
+        })
+    }
+
+    /// the invariant file of the engine, written when the engine proves the model safe
+    fn invariant_path(&self, id: usize) -> PathBuf {
+        self.temp_dir.path().join(format!("invariant{id}.cnf"))
+    }
+
+    pub fn terminate(&mut self) {
+        terminate(&self.result, &self.engine_pids, self.temp_dir.path());
+    }
+
+    /// returns a function that kills all running engines and makes check return unknown,
+    /// it can be called from another thread or a signal handler
+    pub fn terminator(&self) -> impl Fn() + Send + 'static {
+        let result = self.result.clone();
+        let engine_pids = self.engine_pids.clone();
+        let temp_dir = self.temp_dir.path().to_path_buf();
+        move || terminate(&result, &engine_pids, &temp_dir)
+    }
+
+    fn check_inner(&mut self, budget: &Budget) -> Option<bool> {
+        let lock = self.result.0.lock().unwrap();
+        for (mut engine, memory_limit) in take(&mut self.engines) {
+            let args: Vec<String> = engine
+                .get_args()
+                .skip(3)
+                .map(|a| a.to_str().unwrap().to_string())
+                .collect();
+            let config = args.join(" ");
+            if self.option.invariant.is_some() && exports_invariant(&config) {
+                let id = self.status.lock().unwrap().len();
+                engine.arg("--invariant").arg(self.invariant_path(id));
+            }
+            let certificate = if self.option.certificate.is_some()
+                || self.option.certify
+                || self.option.witness
+                || self.option.vcd.is_some()
+            {
+                let certificate = tempfile::NamedTempFile::new_in(self.temp_dir.path()).unwrap();
+                let certify_path = certificate.path().as_os_str().to_str().unwrap();
+                engine.arg(certify_path);
+                Some(certificate)
+            } else {
+                None
+            };
+            let mut child = engine.stderr(Stdio::piped()).spawn().unwrap();
+            self.engine_pids.lock().unwrap().push(child.id() as i32);
+            let id = {
+                let mut status = self.status.lock().unwrap();
+                status.push(EngineStatus {
+                    config: config.clone(),
+                    progress: None,
+                    exit: None,
+                    exited: false,
+                    message: None,
+                });
+                status.len() - 1
+            };
+            let stderr = child.stderr.take().unwrap();
+            let status = self.status.clone();
+            spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    let mut status = status.lock().unwrap();
+                    match heartbeat::parse(&line) {
+                        Some(p) => status[id].progress = Some(p),
+                        None => status[id].message = Some(line),
+                    }
+                }
+            });
+            let option = self.option.clone();
+            let result = self.result.clone();
+            let status = self.status.clone();
+            spawn(move || {
+                if option.verbose > 1 {
+                    println!("start engine: {config}");
+                }
+                #[cfg(target_os = "linux")]
+                let exit = child
+                    .controlled()
+                    .memory_limit(memory_limit)
+                    .wait()
+                    .unwrap()
+                    .unwrap();
+                #[cfg(target_os = "macos")]
+                let exit = {
+                    let _ = memory_limit;
+                    child.controlled().wait().unwrap().unwrap()
+                };
+                {
+                    let mut status = status.lock().unwrap();
+                    status[id].exit = exit.code();
+                    status[id].exited = true;
+                }
+                let mut lock = result.0.lock().unwrap();
+                match exit.code() {
+                    Some(c @ (10 | 20)) => {
+                        if lock.wins(id) {
+                            *lock = PortfolioState::Finished(c == 20, id, args, certificate);
+                        }
+                    }
+                    e => {
+                        if option.verbose > 0 && lock.is_checking() {
+                            println!("{config} unsuccessfully exited, exit code: {:?}", e);
+                        }
+                    }
+                }
+                result.1.notify_one();
+            });
+        }
+        let mut result = lock;
+        let running = |r: &mut PortfolioState| {
+            r.is_checking() && self.status.lock().unwrap().iter().any(|s| !s.exited)
+        };
+        while running(&mut result) {
+            result = self
+                .result
+                .1
+                .wait_timeout_while(result, Duration::from_millis(100), running)
+                .unwrap()
+                .0;
+            if result.is_checking() && budget.exhausted() {
+                *result = PortfolioState::Terminate;
+                kill_engines(&self.engine_pids);
+            }
+        }
+        if result.is_checking() {
+            // all engines exited without a result
+            *result = PortfolioState::Terminate;
+        }
+        if let PortfolioState::Terminate = *result {
+            drop(result);
+            self.print_summary();
+            return None;
+        }
+        // the remaining engines are killed, the earlier engine in the list wins among the
+        // engines finished before all of them are reaped
+        kill_engines(&self.engine_pids);
+        let mut result = self
+            .result
+            .1
+            .wait_while(result, |_| {
+                self.status.lock().unwrap().iter().any(|s| !s.exited)
+            })
+            .unwrap();
+        let (res, id, args, certificate) = result.result();
+        drop(result);
+        self.certificate = certificate;
+        if res && self.option.invariant.is_some() {
+            self.invariant = Some(self.invariant_path(id));
+        }
+        let config = args.join(" ");
+        println!("best configuration: {}", config);
+        if let Some(path) = &self.option.portfolio.save_replay {
+            if let Err(e) = Replay::new(args, &self.option).save(path) {
+                println!("Warning: failed to save replay: {e}");
+            }
+        }
+        self.best_config = Some(config);
+        kill_engines(&self.engine_pids);
+        if self.option.verbose > 0 {
+            self.print_summary();
+        }
+        Some(res)
+    }
+
+    /// prints the last progress and the exit state of each engine
+    fn print_summary(&self) {
+        let status = self.status.lock().unwrap();
+        let width = status.iter().map(|s| s.config.len()).max().unwrap_or(0);
+        println!("{:width$}  {:24}  state", "engine", "progress");
+        for s in status.iter() {
+            let progress = s.progress.map_or("-".to_string(), |p| p.to_string());
+            let state = match s.exit {
+                Some(10) => "unsafe".to_string(),
+                Some(20) => "safe".to_string(),
+                Some(0) => "unknown".to_string(),
+                Some(e) => format!("exit {e}"),
+                None => "killed".to_string(),
+            };
+            println!("{:width$}  {progress:24}  {state}", s.config);
+            if !matches!(s.exit, Some(0 | 10 | 20) | None) {
+                if let Some(m) = &s.message {
+                    println!("{:width$}  {m}", "");
+                }
+            }
+        }
+    }
+}
+
+fn kill_engines(engine_pids: &Mutex<Vec<i32>>) {
+    let mut engine_pids = engine_pids.lock().unwrap();
+    if engine_pids.is_empty() {
+        return;
+    }
+    let pids: Vec<String> = engine_pids.iter().map(|p| format!("{}", *p)).collect();
+    let pid = pids.join(",");
+    let _ = Command::new("pkill")
+        .args(["-9", "--parent", &pid])
+        .output();
+    let mut kill = Command::new("kill");
+    kill.arg("-9");
+    for p in pids {
+        kill.arg(p);
+    }
+    let _ = kill.output();
+    engine_pids.clear();
+}
+
+fn terminate(
+    result: &(Mutex<PortfolioState>, Condvar),
+    engine_pids: &Mutex<Vec<i32>>,
+    temp_dir: &Path,
+) {
+    let Ok(mut lock) = result.0.try_lock() else {
+        return;
+    };
+    if lock.is_checking() {
+        *lock = PortfolioState::Terminate;
+        kill_engines(engine_pids);
+        let _ = Command::new("rm").arg("-rf").arg(temp_dir).output();
+        result.1.notify_one();
+    }
+    drop(lock);
+}
+
+impl Drop for Portfolio {
+    fn drop(&mut self) {
+        kill_engines(&self.engine_pids);
+        let _ = Command::new("rm")
+            .arg("-rf")
+            .arg(self.temp_dir.path())
+            .output();
+    }
+}
+
+impl Engine for Portfolio {
+    fn check(&mut self, budget: &Budget) -> Option<bool> {
+        self.check_inner(budget)
+    }
+
+    fn report(&mut self, report: &mut Report) {
+        report.config = self.best_config.clone();
+        let engines: Vec<serde_json::Value> = self
+            .status
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|s| {
+                serde_json::json!({
+                    "config": s.config,
+                    "progress": s.progress.map(|p| p.to_string()),
+                    "exit": s.exit,
+                })
+            })
+            .collect();
+        report
+            .statistic
+            .insert("engines".to_string(), engines.into());
+    }
+
+    fn certifaiger(&mut self, _aig: &aig::Aig) -> Option<Aig> {
+        let certificate = take(&mut self.certificate)?;
+        Some(Aig::from_file(
+            certificate.path().as_os_str().to_str().unwrap(),
+        ))
+    }
+
+    fn invariant(&mut self) -> Option<Vec<Clause>> {
+        let invariant = fs::read_to_string(take(&mut self.invariant)?).ok()?;
+        invariant::read_dimacs(&invariant).ok()
+    }
+
+    fn witness(&mut self, _aig: &Aig) -> Result<String, Error> {
+        let certificate = take(&mut self.certificate).ok_or(Error::Unsupported("witness"))?;
+        Ok(fs::read_to_string(certificate.path())?)
+    }
+}
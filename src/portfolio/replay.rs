@@ -0,0 +1,80 @@
+use crate::{
+    options::{build, Options},
+    Error,
+};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// the winning configuration of a portfolio run
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Replay {
+    pub version: String,
+    pub commit: String,
+    /// engine arguments without the model and certificate paths
+    pub args: Vec<String>,
+    pub seed: u64,
+}
+
+impl Replay {
+    pub fn new(args: Vec<String>, option: &Options) -> Self {
+        let model = option.model.to_str().unwrap();
+        let seed = Options::try_parse_from(
+            ["rIC3", model]
+                .into_iter()
+                .chain(args.iter().map(|a| a.as_str())),
+        )
+        .map_or(option.rseed, |o| o.rseed);
+        Self {
+            version: build::PKG_VERSION.to_string(),
+            commit: build::SHORT_COMMIT.to_string(),
+            args,
+            seed,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let replay = serde_json::to_string_pretty(self).unwrap();
+        fs::write(path, replay)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let replay = fs::read_to_string(path)?;
+        serde_json::from_str(&replay)
+            .map_err(|e| Error::InvalidInput(format!("invalid replay file: {e}")))
+    }
+
+    /// options of the recorded engine, the model, certificate, verbosity, reporting,
+    /// imported lemmas and sec reference are kept from the given options
+    pub fn options(&self, option: &Options) -> Result<Options, Error> {
+        if self.version != build::PKG_VERSION || self.commit != build::SHORT_COMMIT {
+            println!(
+                "Warning: replay recorded by rIC3 {} ({}), running {} ({})",
+                self.version,
+                self.commit,
+                build::PKG_VERSION,
+                build::SHORT_COMMIT
+            );
+        }
+        let model = option.model.to_str().unwrap();
+        let args = ["rIC3", model]
+            .into_iter()
+            .chain(self.args.iter().map(|a| a.as_str()));
+        let mut res = Options::try_parse_from(args)
+            .map_err(|e| Error::InvalidInput(format!("invalid replay arguments: {e}")))?;
+        res.rseed = self.seed;
+        res.certificate = option.certificate.clone();
+        res.certify = option.certify;
+        res.certify_docker = option.certify_docker;
+        res.witness = option.witness;
+        res.vcd = option.vcd.clone();
+        res.verbose = option.verbose;
+        res.report = option.report.clone();
+        res.budget = option.budget.clone();
+        res.lemmas = option.lemmas.clone();
+        res.prev_model = option.prev_model.clone();
+        res.preprocess.sec = option.preprocess.sec.clone();
+        Ok(res)
+    }
+}
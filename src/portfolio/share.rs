@@ -0,0 +1,80 @@
+use giputils::hash::GHashSet;
+use logic_form::{Clause, Lit};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+/// knowledge shared between the workers of the thread portfolio,
+/// lemmas are over the original aig variables
+#[derive(Default)]
+pub struct Share {
+    lemmas: Mutex<(Vec<(usize, Clause)>, GHashSet<Vec<Lit>>)>,
+    /// one more than the largest depth without counterexample, zero if unknown
+    safe_depth: AtomicUsize,
+    num_worker: AtomicUsize,
+}
+
+impl Share {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn worker(self: &Arc<Self>) -> ShareWorker {
+        ShareWorker {
+            share: self.clone(),
+            id: self.num_worker.fetch_add(1, Ordering::Relaxed),
+            cursor: 0,
+        }
+    }
+
+    pub fn num_lemmas(&self) -> usize {
+        self.lemmas.lock().unwrap().0.len()
+    }
+}
+
+/// the handle of a worker to the shared knowledge
+pub struct ShareWorker {
+    share: Arc<Share>,
+    id: usize,
+    cursor: usize,
+}
+
+impl ShareWorker {
+    /// publishes lemmas that hold in all reachable states within the frames of the worker,
+    /// importers have to validate them
+    pub fn publish_lemmas(&self, lemmas: impl IntoIterator<Item = Clause>) {
+        let mut pool = self.share.lemmas.lock().unwrap();
+        let (list, set) = &mut *pool;
+        for l in lemmas {
+            let mut key: Vec<Lit> = l.iter().copied().collect();
+            key.sort();
+            if set.insert(key) {
+                list.push((self.id, l));
+            }
+        }
+    }
+
+    /// lemmas published by other workers since the last import
+    pub fn import_lemmas(&mut self) -> Vec<Clause> {
+        let pool = self.share.lemmas.lock().unwrap();
+        let res = pool.0[self.cursor..]
+            .iter()
+            .filter(|(id, _)| *id != self.id)
+            .map(|(_, l)| l.clone())
+            .collect();
+        self.cursor = pool.0.len();
+        res
+    }
+
+    /// no bad state is reachable within the depth
+    pub fn publish_safe_depth(&self, depth: usize) {
+        self.share
+            .safe_depth
+            .fetch_max(depth + 1, Ordering::Relaxed);
+    }
+
+    pub fn safe_depth(&self) -> Option<usize> {
+        self.share.safe_depth.load(Ordering::Relaxed).checked_sub(1)
+    }
+}
@@ -0,0 +1,237 @@
+use super::{replay::Replay, share::Share, PortfolioConfig};
+use crate::{
+    abs::Localization, bmc::BMC, budget::Budget, frontend::aig::liveness_to_safety, ic3::IC3,
+    imc::IMC, kind::Kind, options, pre_lemmas, report::Report, sec_lemmas, transys, Engine, Error,
+    Options,
+};
+use aig::Aig;
+use clap::Parser;
+use logic_form::Clause;
+use std::{
+    mem::take,
+    sync::{Arc, Condvar, Mutex},
+    thread::spawn,
+    time::Duration,
+};
+
+struct Winner {
+    id: usize,
+    res: bool,
+    config: String,
+    certifaiger: Option<Aig>,
+    witness: Option<Result<String, Error>>,
+    invariant: Option<Vec<Clause>>,
+}
+
+struct ThreadState {
+    winner: Option<Winner>,
+    exited: Vec<bool>,
+}
+
+impl ThreadState {
+    /// the result is decided once every worker before the winner in the list has exited,
+    /// the earlier engine wins a tie regardless of the finishing order
+    fn decided(&self) -> bool {
+        match &self.winner {
+            Some(w) => self.exited[..w.id].iter().all(|e| *e),
+            None => self.exited.iter().all(|e| *e),
+        }
+    }
+}
+
+/// marks the worker as exited even if the engine panics
+struct ExitGuard(Arc<(Mutex<ThreadState>, Condvar)>, usize);
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        let mut state = self.0 .0.lock().unwrap_or_else(|e| e.into_inner());
+        state.exited[self.1] = true;
+        self.0 .1.notify_one();
+    }
+}
+
+/// portfolio running engines as threads of this process, ic3 workers share lemmas
+/// and bmc workers share the depths without counterexample. the workers are not joined,
+/// they stop at their next safe point after a result is found
+pub struct ThreadPortfolio {
+    option: Options,
+    aig: Aig,
+    configs: Vec<String>,
+    share: Arc<Share>,
+    winner: Option<Winner>,
+}
+
+impl ThreadPortfolio {
+    pub fn new(option: Options, aig: &Aig) -> Result<Self, Error> {
+        let configs = PortfolioConfig::from_options(&option)?
+            .engines
+            .into_iter()
+            .map(|e| e.args)
+            .collect();
+        Ok(Self {
+            option,
+            aig: aig.clone(),
+            configs,
+            share: Share::new(),
+            winner: None,
+        })
+    }
+
+    fn worker_options(&self, config: &str) -> Options {
+        let model = self.option.model.to_str().unwrap();
+        let args = ["rIC3", model, "-v", "0"]
+            .into_iter()
+            .chain(config.split_whitespace());
+        let mut options = Options::try_parse_from(args)
+            .unwrap_or_else(|e| panic!("Error: invalid portfolio engine \"{config}\": {e}"));
+        if let options::Engine::Portfolio = options.engine {
+            panic!("Error: portfolio engine \"{config}\" can not be a portfolio");
+        }
+        options.certificate = self.option.certificate.clone();
+        options.certify = self.option.certify;
+        options.witness = self.option.witness;
+        options.invariant = self.option.invariant.clone();
+        options.budget = self.option.budget.clone();
+        options.lemmas = self.option.lemmas.clone();
+        options.prev_model = self.option.prev_model.clone();
+        options.preprocess.sec = self.option.preprocess.sec.clone();
+        options
+    }
+}
+
+impl Engine for ThreadPortfolio {
+    fn check(&mut self, budget: &Budget) -> Option<bool> {
+        let state = Arc::new((
+            Mutex::new(ThreadState {
+                winner: None,
+                exited: vec![false; self.configs.len()],
+            }),
+            Condvar::new(),
+        ));
+        let worker_budget = budget.child();
+        let cert = self.option.certificate.is_some()
+            || self.option.certify
+            || self.option.witness
+            || self.option.vcd.is_some();
+        let export_invariant = self.option.invariant.is_some();
+        for (id, config) in self.configs.iter().enumerate() {
+            let options = self.worker_options(config);
+            let config = config.clone();
+            let aig = self.aig.clone();
+            let share = self.share.worker();
+            let budget = worker_budget.clone();
+            let state = state.clone();
+            if self.option.verbose > 1 {
+                println!("start engine: {config}");
+            }
+            spawn(move || {
+                let _guard = ExitGuard(state.clone(), id);
+                let mut model = aig.clone();
+                if !model.justice.is_empty() {
+                    model = liveness_to_safety(&model);
+                }
+                if model.bads.len() > 1 {
+                    model.compress_property();
+                }
+                let ts = transys(&options, &model).unwrap_or_else(|e| panic!("Error: {e}"));
+                let mut engine: Box<dyn Engine> = match options.engine {
+                    options::Engine::IC3 => {
+                        let mut lemmas = pre_lemmas(&options, &model, &ts)
+                            .unwrap_or_else(|e| panic!("Error: failed to import lemmas: {e}"));
+                        if options.preprocess.sec.is_some() {
+                            lemmas.extend(sec_lemmas(&options, &ts));
+                        }
+                        let mut ic3 = IC3::new(options.clone(), ts, lemmas);
+                        ic3.set_share(share);
+                        Box::new(ic3)
+                    }
+                    options::Engine::Kind => {
+                        let mut kind = Kind::new(options.clone(), ts);
+                        kind.set_share(share);
+                        Box::new(kind)
+                    }
+                    options::Engine::BMC => {
+                        let mut bmc = BMC::new(options.clone(), ts);
+                        bmc.set_share(share);
+                        Box::new(bmc)
+                    }
+                    options::Engine::Abs => Box::new(Localization::new(options.clone(), ts)),
+                    options::Engine::IMC => Box::new(IMC::new(options.clone(), ts)),
+                    options::Engine::Portfolio => unreachable!(),
+                };
+                let Some(res) = engine.check(&budget) else {
+                    return;
+                };
+                let mut lock = state.0.lock().unwrap();
+                // the earlier engine in the list wins a tie
+                if lock.winner.as_ref().is_some_and(|w| w.id < id) {
+                    return;
+                }
+                budget.cancel();
+                let (certifaiger, witness) = match (cert, res) {
+                    (false, _) => (None, None),
+                    (true, true) => (engine.certifaiger(&aig), None),
+                    (true, false) => (None, Some(engine.witness(&aig))),
+                };
+                let invariant = if export_invariant && res {
+                    engine.invariant()
+                } else {
+                    None
+                };
+                lock.winner = Some(Winner {
+                    id,
+                    res,
+                    config,
+                    certifaiger,
+                    witness,
+                    invariant,
+                });
+                state.1.notify_one();
+            });
+        }
+        let mut lock = state.0.lock().unwrap();
+        while !lock.decided() {
+            lock = state
+                .1
+                .wait_timeout(lock, Duration::from_millis(100))
+                .unwrap()
+                .0;
+            if budget.exhausted() {
+                break;
+            }
+        }
+        worker_budget.cancel();
+        self.winner = take(&mut lock.winner);
+        let winner = self.winner.as_ref()?;
+        println!("best configuration: {}", winner.config);
+        if let Some(path) = &self.option.portfolio.save_replay {
+            let args = winner.config.split_whitespace().map(String::from).collect();
+            if let Err(e) = Replay::new(args, &self.option).save(path) {
+                println!("Warning: failed to save replay: {e}");
+            }
+        }
+        if self.option.verbose > 0 {
+            println!("shared lemmas: {}", self.share.num_lemmas());
+        }
+        Some(winner.res)
+    }
+
+    fn certifaiger(&mut self, _aig: &Aig) -> Option<Aig> {
+        take(&mut self.winner.as_mut()?.certifaiger)
+    }
+
+    fn witness(&mut self, _aig: &Aig) -> Result<String, Error> {
+        self.winner
+            .as_mut()
+            .and_then(|w| take(&mut w.witness))
+            .unwrap_or(Err(Error::Unsupported("witness")))
+    }
+
+    fn invariant(&mut self) -> Option<Vec<Clause>> {
+        take(&mut self.winner.as_mut()?.invariant)
+    }
+
+    fn report(&mut self, report: &mut Report) {
+        report.config = self.winner.as_ref().map(|w| w.config.clone());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::options::Options;
+use serde_json::{json, Map, Value};
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs, io,
+    ops::AddAssign,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// machine-readable summary of a check, written by --report json
+pub struct Report {
+    start: Instant,
+    pub verdict: Option<bool>,
+    pub engine: String,
+    /// the configuration that produced the verdict
+    pub config: Option<String>,
+    /// depth of bmc and k-induction, or frame of ic3
+    pub bound: Option<usize>,
+    pub invariant_size: Option<usize>,
+    /// per-property verdicts in multi-property mode
+    pub properties: Option<Vec<Option<bool>>>,
+    /// engine statistics, grouped by their source
+    pub statistic: Map<String, Value>,
+}
+
+impl Report {
+    pub fn new(options: &Options) -> Self {
+        Self {
+            start: Instant::now(),
+            verdict: None,
+            engine: format!("{:?}", options.engine).to_lowercase(),
+            config: None,
+            bound: None,
+            invariant_size: None,
+            properties: None,
+            statistic: Map::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let verdict = |v: Option<bool>| match v {
+            Some(true) => "safe",
+            Some(false) => "unsafe",
+            None => "unknown",
+        };
+        let mut report = json!({
+            "verdict": verdict(self.verdict),
+            "engine": self.engine,
+            "config": self.config,
+            "bound": self.bound,
+            "invariant_size": self.invariant_size,
+            "wall_time": self.start.elapsed().as_secs_f64(),
+            "statistic": self.statistic,
+        });
+        if let Some(properties) = &self.properties {
+            report["properties"] = properties.iter().map(|p| verdict(*p)).collect();
+        }
+        report
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(&self.to_json())?)
+    }
+}
+
+/// running average of a statistic
+#[derive(Default, Clone, Copy)]
+pub struct Average {
+    sum: f64,
+    num: usize,
+}
+
+impl Average {
+    pub fn value(&self) -> f64 {
+        if self.num == 0 {
+            0.0
+        } else {
+            self.sum / self.num as f64
+        }
+    }
+
+    pub fn json(&self) -> Value {
+        json!({ "avg": self.value(), "num": self.num })
+    }
+}
+
+impl AddAssign<f64> for Average {
+    fn add_assign(&mut self, rhs: f64) {
+        self.sum += rhs;
+        self.num += 1;
+    }
+}
+
+impl AddAssign<usize> for Average {
+    fn add_assign(&mut self, rhs: usize) {
+        *self += rhs as f64;
+    }
+}
+
+impl AddAssign for Average {
+    fn add_assign(&mut self, rhs: Self) {
+        self.sum += rhs.sum;
+        self.num += rhs.num;
+    }
+}
+
+impl Debug for Average {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2}", self.value())
+    }
+}
+
+/// success and failure counts of an attempt
+#[derive(Default, Clone, Copy)]
+pub struct SuccessRate {
+    succ: usize,
+    fail: usize,
+}
+
+impl SuccessRate {
+    pub fn success(&mut self) {
+        self.succ += 1;
+    }
+
+    pub fn fail(&mut self) {
+        self.fail += 1;
+    }
+
+    pub fn statistic(&mut self, success: bool) {
+        if success {
+            self.success();
+        } else {
+            self.fail();
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        let total = self.succ + self.fail;
+        if total == 0 {
+            0.0
+        } else {
+            self.succ as f64 / total as f64
+        }
+    }
+
+    pub fn json(&self) -> Value {
+        json!({ "success": self.succ, "fail": self.fail, "rate": self.rate() })
+    }
+}
+
+impl Debug for SuccessRate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "success: {}, fail: {}, success rate: {:.2}%",
+            self.succ,
+            self.fail,
+            self.rate() * 100.0
+        )
+    }
+}
+
+/// time since the start of an engine
+#[derive(Clone, Copy)]
+pub struct RunningTime(Instant);
+
+impl RunningTime {
+    pub fn secs(&self) -> f64 {
+        self.0.elapsed().as_secs_f64()
+    }
+}
+
+impl Default for RunningTime {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Debug for RunningTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0.elapsed())
+    }
+}
+
+/// the number and time of sat solver calls, for engines driving a single solver
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SolveStatistic {
+    pub num_solve: usize,
+    pub solve_time: Duration,
+}
+
+impl SolveStatistic {
+    /// counts and times a solver call
+    pub fn solve<T>(&mut self, solve: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let res = solve();
+        self.num_solve += 1;
+        self.solve_time += start.elapsed();
+        res
+    }
+
+    pub fn json(&self) -> Value {
+        json!({
+            "num_solve": self.num_solve,
+            "solve_time": self.solve_time.as_secs_f64(),
+        })
+    }
+}
@@ -90,6 +90,8 @@ impl TransysBuilder {
             dependence: self.rel.dep,
             max_latch,
             restore: self.rst.clone(),
+            merged: Vec::new(),
+            retimed: false,
         }
     }
 }
@@ -1,4 +1,5 @@
 pub mod builder;
+pub mod scorr;
 pub mod simplify;
 pub mod simulate;
 pub mod unroll;
@@ -23,6 +24,11 @@ pub struct Transys {
     pub dependence: VarMap<Vec<Var>>,
     pub max_latch: Var,
     restore: GHashMap<Var, Var>,
+    /// latchs of the original aig merged into a literal of the transys, they are restored
+    /// from the literal, a literal over the constant variable for constant latchs
+    merged: Vec<(Var, Lit)>,
+    /// the latchs are retimed from the original aig and have no origin
+    pub retimed: bool,
 }
 
 impl Transys {
@@ -165,6 +171,111 @@ impl Transys {
         Lit::new(var, lit.polarity())
     }
 
+    #[inline]
+    pub fn try_restore(&self, lit: Lit) -> Option<Lit> {
+        self.restore
+            .get(&lit.var())
+            .map(|v| Lit::new(*v, lit.polarity()))
+    }
+
+    /// the values of the original aig latchs in a state of the transys, including the
+    /// latchs merged by preprocessing, retimed latchs without origin are skipped
+    pub fn restore_state(&self, mut value: impl FnMut(Lit) -> Option<bool>) -> Cube {
+        let mut res = Cube::new();
+        for l in self.latchs.iter() {
+            let l = l.lit();
+            let Some(v) = value(l) else {
+                continue;
+            };
+            if self.retimed {
+                res.extend(self.try_restore(l.not_if(!v)));
+            } else {
+                res.push(self.restore(l.not_if(!v)));
+            }
+        }
+        for (o, r) in self.merged.iter() {
+            let v = if r.var() == Var::new(0) {
+                Some(*r == Lit::constant(true))
+            } else {
+                value(*r)
+            };
+            if let Some(v) = v {
+                res.push(o.lit().not_if(!v));
+            }
+        }
+        res
+    }
+
+    /// clauses over the original aig variables that define the merged latchs
+    pub fn merged_clauses(&self) -> Vec<Clause> {
+        let mut res = Vec::new();
+        for (o, r) in self.merged.iter() {
+            let o = o.lit();
+            if r.var() == Var::new(0) {
+                res.push(Clause::from([o.not_if(*r != Lit::constant(true))]));
+            } else if let Some(r) = self.try_restore(*r) {
+                res.push(Clause::from([!o, r]));
+                res.push(Clause::from([o, !r]));
+            }
+        }
+        res
+    }
+
+    #[inline]
+    pub fn has_merged(&self) -> bool {
+        !self.merged.is_empty()
+    }
+
+    /// maps clauses over the original aig latchs into the transys,
+    /// clauses containing latchs removed by preprocessing are dropped
+    pub fn lemmas_from_origin(&self, lemmas: &[Clause]) -> Vec<Clause> {
+        let map: GHashMap<Var, Var> = self
+            .latchs
+            .iter()
+            .filter_map(|l| self.restore.get(l).map(|r| (*r, *l)))
+            .collect();
+        lemmas
+            .iter()
+            .filter_map(|c| {
+                c.iter()
+                    .map(|l| map.get(&l.var()).map(|v| Lit::new(*v, l.polarity())))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// the largest subset of the lemmas that holds in the initial states and is inductive
+    /// together, lemmas failing initiation or consecution are dropped until a fixpoint
+    pub fn inductive_subset(&self, mut lemmas: Vec<Clause>) -> Vec<Clause> {
+        lemmas.retain(|c| !self.cube_subsume_init(&!c.clone()));
+        loop {
+            let mut solver = satif_minisat::Solver::new();
+            self.load_trans(&mut solver, true);
+            for c in lemmas.iter() {
+                solver.add_clause(c);
+            }
+            let len = lemmas.len();
+            lemmas.retain(|c| !solver.solve(&self.cube_next(&!c.clone())));
+            if lemmas.len() == len {
+                return lemmas;
+            }
+        }
+    }
+
+    /// the localization abstraction keeping the visible latchs, all other latchs become
+    /// free inputs
+    pub fn abstract_latchs(&self, visible: &GHashSet<Var>) -> Self {
+        let mut res = self.clone();
+        for l in self.latchs.iter().filter(|l| !visible.contains(l)) {
+            res.is_latch[*l] = false;
+            res.init_map[*l] = None;
+            res.inputs.push(*l);
+        }
+        res.latchs.retain(|l| visible.contains(l));
+        res.init.retain(|l| visible.contains(&l.var()));
+        res
+    }
+
     pub fn print_info(&self) {
         println!("num input: {}", self.inputs.len());
         println!("num latch: {}", self.latchs.len());
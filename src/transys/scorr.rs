@@ -0,0 +1,100 @@
+use super::{simulate::Correspondence, unroll::TransysUnroll, Transys};
+use logic_form::{Clause, Cube, Lit, Var};
+use satif::Satif;
+use satif_cadical::Solver;
+
+impl Transys {
+    /// equivalent and constant latchs, the candidates found by simulation that hold in the
+    /// first k steps from the initial states and are k-inductive together, failing
+    /// candidates are dropped until a fixpoint. with k = 1 their clauses are inductive
+    pub fn latch_correspondences(&self, k: usize) -> Vec<Correspondence> {
+        let mut candidates = self.correspondence_candidates();
+        let mut uts = TransysUnroll::new(self);
+        uts.unroll_to(k);
+        let mut solver = Solver::new();
+        self.load_init(&mut solver);
+        for u in 0..k {
+            uts.load_trans(&mut solver, u, true);
+        }
+        candidates.retain(|c| {
+            c.clauses().iter().all(|cls| {
+                let cube = !cls.clone();
+                (0..k).all(|u| !solver.solve(&uts.lits_next(&cube, u)))
+            })
+        });
+        loop {
+            let mut solver = Solver::new();
+            for u in 0..=k {
+                uts.load_trans(&mut solver, u, true);
+            }
+            for c in candidates.iter() {
+                for cls in c.clauses() {
+                    for u in 0..k {
+                        solver.add_clause(&uts.lits_next(&cls, u));
+                    }
+                }
+            }
+            let len = candidates.len();
+            candidates.retain(|c| {
+                c.clauses().iter().all(|cls| {
+                    let cube: Cube = !cls.clone();
+                    !solver.solve(&uts.lits_next(&cube, k))
+                })
+            });
+            if candidates.len() == len {
+                return candidates;
+            }
+        }
+    }
+
+    /// removes the latch from the transys, it is defined as the representative literal in
+    /// trans and restored from it
+    fn merge_latch(&mut self, latch: Var, repr: Lit) {
+        if let Some(i) = self.init_map[latch] {
+            if !self.is_latch(repr.var()) {
+                assert!(repr == Lit::constant(i));
+            } else if self.init_map[repr.var()].is_none() {
+                self.add_init(repr.var(), Some(i == repr.polarity()));
+            }
+        }
+        self.latchs.retain(|l| *l != latch);
+        self.is_latch[latch] = false;
+        self.init_map[latch] = None;
+        self.init.retain(|l| l.var() != latch);
+        let l = latch.lit();
+        if self.is_latch(repr.var()) {
+            self.trans.push(Clause::from([!l, repr]));
+            self.trans.push(Clause::from([l, !repr]));
+            self.dependence[latch] = vec![repr.var()];
+        } else {
+            self.trans
+                .push(Clause::from([l.not_if(repr != Lit::constant(true))]));
+            self.dependence[latch].clear();
+        }
+        if let Some(o) = self.restore.remove(&latch) {
+            self.merged.push((o, repr));
+        }
+    }
+
+    /// merges latchs that are constant in all reachable states into the constant,
+    /// returns the number of merged latchs
+    pub fn remove_constant_latchs(&mut self, constants: &[Lit]) -> usize {
+        for c in constants.iter() {
+            self.merge_latch(c.var(), Lit::constant(c.polarity()));
+        }
+        constants.len()
+    }
+
+    /// signal correspondence, latchs that are equivalent or constant in simulation and
+    /// proved by k-induction are merged into their representatives, returns the number
+    /// of merged latchs
+    pub fn signal_correspondence(&mut self, k: usize) -> usize {
+        let proved = self.latch_correspondences(k.max(1));
+        for c in proved.iter() {
+            // c.lit is equal to repr, so its variable is equal to repr under the polarity of c.lit
+            let repr = c.repr.unwrap_or(Lit::constant(false));
+            self.merge_latch(c.lit.var(), repr.not_if(!c.lit.polarity()));
+        }
+        proved.len()
+    }
+}
@@ -149,6 +149,7 @@ impl Transys {
                 restore.insert(domain_map[d], *r);
             }
         }
+        let merged = self.merged.iter().map(|(o, r)| (*o, map_lit(r))).collect();
         Self {
             inputs,
             latchs,
@@ -164,6 +165,7 @@ impl Transys {
             dependence,
             max_latch,
             restore,
+            merged,
         }
     }
 }
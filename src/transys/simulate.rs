@@ -1,7 +1,7 @@
 use super::Transys;
 use crate::transys::unroll::TransysUnroll;
 use giputils::hash::GHashMap;
-use logic_form::{Cube, Var};
+use logic_form::{Clause, Cube, Lit, Var};
 use satif::Satif;
 use satif_cadical::Solver;
 
@@ -61,4 +61,54 @@ impl Transys {
         }
         Some(bv)
     }
+
+    /// candidate equivalent and constant latchs with the same or the opposite values in all
+    /// simulations, they are proved by latch_correspondences
+    pub(super) fn correspondence_candidates(&self) -> Vec<Correspondence> {
+        let simulation = self.simulations();
+        let num_sim = simulation.len();
+        if num_sim == 0 {
+            return vec![];
+        }
+        let Some(bv) = self.simulation_bv(simulation) else {
+            return vec![];
+        };
+        let mask = u64::MAX >> (64 - num_sim);
+        // latchs are normalized to be false in the first simulation
+        let mut classes: GHashMap<u64, Vec<Lit>> = GHashMap::new();
+        for l in self.latchs.iter() {
+            let v = bv[l];
+            let (key, lit) = if v & 1 == 1 {
+                (!v & mask, !l.lit())
+            } else {
+                (v, l.lit())
+            };
+            classes.entry(key).or_default().push(lit);
+        }
+        let mut res = vec![];
+        for (key, class) in classes {
+            let repr = (key != 0).then_some(class[0]);
+            let skip = if repr.is_some() { 1 } else { 0 };
+            for lit in class[skip..].iter() {
+                res.push(Correspondence { repr, lit: *lit });
+            }
+        }
+        res
+    }
+}
+
+/// a latch literal equal to a representative literal, or constant false without one
+#[derive(Clone, Copy, Debug)]
+pub struct Correspondence {
+    pub repr: Option<Lit>,
+    pub lit: Lit,
+}
+
+impl Correspondence {
+    pub fn clauses(&self) -> Vec<Clause> {
+        match self.repr {
+            Some(r) => vec![Clause::from([!r, self.lit]), Clause::from([r, !self.lit])],
+            None => vec![Clause::from([!self.lit])],
+        }
+    }
 }
@@ -29,7 +29,7 @@ impl TransysUnroll {
         }
         for l in ts.latchs.iter() {
             let l = l.lit();
-             // using the next map of input ts
+            // using the next map of input ts
             let next = ts.lit_next(l);
             next_map[l].push(next);
             next_map[!l].push(!next);
@@ -216,6 +216,8 @@ impl TransysUnroll {
             max_latch: self.ts.max_latch,
             is_latch: self.ts.is_latch.clone(),
             restore: GHashMap::new(),
+            merged: Vec::new(),
+            retimed: self.ts.retimed,
         }
     }
 
@@ -321,6 +323,8 @@ impl TransysUnroll {
             max_latch,
             is_latch,
             restore: self.ts.restore.clone(),
+            merged: self.ts.merged.clone(),
+            retimed: self.ts.retimed,
         }
     }
 }
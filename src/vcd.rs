@@ -0,0 +1,274 @@
+use crate::{certify::parse_values, invariant::symbol};
+use aig::{Aig, AigEdge, TernarySimulate};
+use giputils::hash::{GHashMap, GHashSet};
+use logic_form::Lbool;
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Error, ErrorKind},
+    path::Path,
+};
+
+/// a vcd variable, the bits of a bus are grouped by the `name[i]` symbols
+struct Signal {
+    name: String,
+    /// least significant bit first, none if the bit is missing in the bus
+    bits: Vec<Option<AigEdge>>,
+}
+
+/// groups named edges into signals, `name[i]` names become bits of the bus `name`
+fn signals(named: impl Iterator<Item = (String, AigEdge)>) -> Vec<Signal> {
+    let mut res: Vec<Signal> = Vec::new();
+    let mut buses: GHashMap<String, usize> = GHashMap::new();
+    for (name, e) in named {
+        let name = name.replace(char::is_whitespace, "_");
+        let bit = name
+            .strip_suffix(']')
+            .and_then(|n| n.rsplit_once('['))
+            .and_then(|(n, i)| Some((n.to_string(), i.parse::<usize>().ok()?)));
+        let Some((bus, i)) = bit else {
+            res.push(Signal {
+                name,
+                bits: vec![Some(e)],
+            });
+            continue;
+        };
+        let s = *buses.entry(bus.clone()).or_insert_with(|| {
+            res.push(Signal {
+                name: bus,
+                bits: Vec::new(),
+            });
+            res.len() - 1
+        });
+        let bits = &mut res[s].bits;
+        if bits.len() <= i {
+            bits.resize(i + 1, None);
+        }
+        bits[i] = Some(e);
+    }
+    // a scalar named as a bus is renamed, buses keep their names
+    let bus: GHashSet<usize> = buses.values().copied().collect();
+    let mut used: GHashSet<String> = buses.into_keys().collect();
+    for (_, s) in res.iter_mut().enumerate().filter(|(i, _)| !bus.contains(i)) {
+        let mut name = s.name.clone();
+        let mut n = 1;
+        while !used.insert(name.clone()) {
+            name = format!("{}_{n}", s.name);
+            n += 1;
+        }
+        s.name = name;
+    }
+    res
+}
+
+fn next_line<'a>(data: &'a [u8], pos: &mut usize) -> Option<&'a str> {
+    let rest = data.get(*pos..).filter(|r| !r.is_empty())?;
+    let end = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+    *pos += end + 1;
+    std::str::from_utf8(&rest[..end]).ok()
+}
+
+fn skip_varint(data: &[u8], pos: &mut usize) -> Option<()> {
+    while *data.get(*pos)? & 0x80 != 0 {
+        *pos += 1;
+    }
+    *pos += 1;
+    Some(())
+}
+
+/// the output and bad symbols of an aiger file with their literals, none if the model is
+/// not an aiger file. the aig reader keeps only the input and latch symbols
+fn property_symbols(data: &[u8]) -> Option<Vec<(AigEdge, String)>> {
+    let mut pos = 0;
+    let mut header = next_line(data, &mut pos)?.split_whitespace();
+    let binary = match header.next()? {
+        "aag" => false,
+        "aig" => true,
+        _ => return None,
+    };
+    let mut num: Vec<usize> = header.map(|n| n.parse().ok()).collect::<Option<_>>()?;
+    if num.len() < 9 {
+        num.resize(9, 0);
+    }
+    let [_, i, l, o, a, b, c, j, f] = num[..9] else {
+        return None;
+    };
+    let literals = |n: usize, pos: &mut usize| -> Option<Vec<usize>> {
+        (0..n)
+            .map(|_| {
+                next_line(data, pos)?
+                    .split_whitespace()
+                    .next()?
+                    .parse()
+                    .ok()
+            })
+            .collect()
+    };
+    if !binary {
+        literals(i, &mut pos)?;
+    }
+    literals(l, &mut pos)?;
+    let outputs = literals(o, &mut pos)?;
+    let bads = literals(b, &mut pos)?;
+    literals(c, &mut pos)?;
+    let justice: usize = literals(j, &mut pos)?.into_iter().sum();
+    literals(justice, &mut pos)?;
+    literals(f, &mut pos)?;
+    if binary {
+        for _ in 0..2 * a {
+            skip_varint(data, &mut pos)?;
+        }
+    } else {
+        literals(a, &mut pos)?;
+    }
+    let mut res = Vec::new();
+    while let Some(line) = next_line(data, &mut pos) {
+        if line == "c" {
+            break;
+        }
+        let Some((id, name)) = line.split_once(' ') else {
+            continue;
+        };
+        let (kind, id) = id.split_at(1);
+        let lits = match kind {
+            "o" => &outputs,
+            "b" => &bads,
+            _ => continue,
+        };
+        if let Some(l) = id.parse::<usize>().ok().and_then(|i| lits.get(i)) {
+            res.push((AigEdge::new(l / 2, l % 2 == 1), name.to_string()));
+        }
+    }
+    Some(res)
+}
+
+/// the vcd identifier code of the i-th variable
+fn identifier(mut i: usize) -> String {
+    let mut id = String::new();
+    loop {
+        id.push((b'!' + (i % 94) as u8) as char);
+        i /= 94;
+        if i == 0 {
+            return id;
+        }
+        i -= 1;
+    }
+}
+
+fn value(simulate: &TernarySimulate, signal: &Signal) -> String {
+    let bit = |b: &Option<AigEdge>| {
+        let v = b.map_or(Lbool::NONE, |b| simulate.value(b));
+        if v.is_true() {
+            '1'
+        } else if v == Lbool::NONE {
+            'x'
+        } else {
+            '0'
+        }
+    };
+    if signal.bits.len() == 1 {
+        bit(&signal.bits[0]).to_string()
+    } else {
+        let bits: String = signal.bits.iter().rev().map(bit).collect();
+        format!("b{bits} ")
+    }
+}
+
+/// simulates an aiger witness on the aig and writes the trace of the inputs, latchs,
+/// outputs and bad properties as vcd waveforms, named by the symbol table of the model.
+/// the properties are matched by their literals, as the checked aig may move outputs to
+/// bad properties or keep a single one of them
+pub fn write_vcd(aig: &Aig, model: &Path, witness: &str, path: &Path) -> io::Result<()> {
+    let invalid = |e: String| Error::new(ErrorKind::InvalidData, e);
+    let mut lines = witness
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('c'))
+        .skip(2);
+    let state = lines
+        .next()
+        .ok_or_else(|| invalid("missing initial state".to_string()))?;
+    let state = parse_values(state, aig.latchs.len()).map_err(|e| invalid(e.to_string()))?;
+    let frames: Vec<Vec<Lbool>> = lines
+        .take_while(|l| *l != ".")
+        .map(|l| parse_values(l, aig.inputs.len()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| invalid(e.to_string()))?;
+    let symbols = property_symbols(&fs::read(model)?).unwrap_or_default();
+    let property = |e: &AigEdge, default: String| {
+        symbols
+            .iter()
+            .find(|(s, _)| s == e)
+            .map_or(default, |(_, n)| n.clone())
+    };
+    let scopes = [
+        (
+            "inputs",
+            signals(
+                aig.inputs
+                    .iter()
+                    .map(|i| (symbol(aig, *i), AigEdge::from(*i))),
+            ),
+        ),
+        (
+            "latchs",
+            signals(
+                aig.latchs
+                    .iter()
+                    .map(|l| (symbol(aig, l.input), AigEdge::from(l.input))),
+            ),
+        ),
+        (
+            "outputs",
+            signals(
+                aig.outputs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, o)| (property(o, format!("o{i}")), *o))
+                    .chain(
+                        aig.bads
+                            .iter()
+                            .enumerate()
+                            .map(|(i, b)| (property(b, format!("b{i}")), *b)),
+                    ),
+            ),
+        ),
+    ];
+    let mut vcd = String::new();
+    writeln!(vcd, "$version rIC3 $end").unwrap();
+    writeln!(vcd, "$timescale 1ns $end").unwrap();
+    writeln!(vcd, "$scope module top $end").unwrap();
+    let mut id = 0;
+    for (scope, signals) in scopes.iter() {
+        writeln!(vcd, "$scope module {scope} $end").unwrap();
+        for s in signals.iter() {
+            writeln!(
+                vcd,
+                "$var wire {} {} {} $end",
+                s.bits.len(),
+                identifier(id),
+                s.name
+            )
+            .unwrap();
+            id += 1;
+        }
+        writeln!(vcd, "$upscope $end").unwrap();
+    }
+    writeln!(vcd, "$upscope $end").unwrap();
+    writeln!(vcd, "$enddefinitions $end").unwrap();
+    let signals: Vec<&Signal> = scopes.iter().flat_map(|(_, s)| s.iter()).collect();
+    let mut last = vec![String::new(); signals.len()];
+    let mut simulate = TernarySimulate::new(aig, state);
+    for (k, input) in frames.into_iter().enumerate() {
+        simulate.simulate(input);
+        writeln!(vcd, "#{k}").unwrap();
+        for (i, s) in signals.iter().enumerate() {
+            let v = value(&simulate, s);
+            if v != last[i] {
+                writeln!(vcd, "{v}{}", identifier(i)).unwrap();
+                last[i] = v;
+            }
+        }
+    }
+    fs::write(path, vcd)
+}
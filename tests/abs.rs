@@ -0,0 +1,19 @@
+mod common;
+
+use common::{certified, verdict};
+
+#[test]
+fn abs_finds_counterexample() {
+    assert_eq!(verdict(&["-e", "abs"], "counter_unsafe.aag"), Some(false));
+    assert_eq!(certified(&["-e", "abs"], "enable_counter.aag"), Some(false));
+}
+
+/// the refined abstractions are seeded with the lemmas of the previous ones
+#[test]
+fn abs_proves_safe() {
+    assert_eq!(certified(&["-e", "abs"], "mod3_safe.aag"), Some(true));
+    assert_eq!(
+        verdict(&["-e", "abs", "--no-abc"], "unreachable_loop.aag"),
+        Some(true)
+    );
+}
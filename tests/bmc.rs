@@ -0,0 +1,63 @@
+mod common;
+
+use common::{load, options, verdict};
+use rIC3::{
+    bmc::BMC, budget::Budget, check, check_witness, kind::Kind, portfolio::share::Share, transys,
+    Engine, Verdict,
+};
+
+#[test]
+fn bmc_finds_counterexample() {
+    assert_eq!(verdict(&["-e", "bmc"], "counter_unsafe.aag"), Some(false));
+    assert_eq!(
+        verdict(&["-e", "bmc", "--max-depth", "8"], "mod3_safe.aag"),
+        None
+    );
+}
+
+/// a depth that times out must not be published as safe, otherwise k-induction
+/// skips its base case and proves the unsafe counter
+#[test]
+fn timed_out_depth_is_not_shared() {
+    let model = "counter_unsafe.aag";
+    let aig = load(model);
+    let share = Share::new();
+    let options = options(
+        &["-e", "bmc", "--bmc-time-limit", "0", "--max-depth", "8"],
+        model,
+    );
+    let mut bmc = BMC::new(options.clone(), transys(&options, &aig).unwrap());
+    bmc.set_share(share.worker());
+    let res = bmc.check(&Budget::new(&options));
+    assert_ne!(res, Some(true));
+    let options = self::options(&["-e", "kind", "--max-depth", "12"], model);
+    let mut kind = Kind::new(options.clone(), transys(&options, &aig).unwrap());
+    kind.set_share(share.worker());
+    assert_ne!(kind.check(&Budget::new(&options)), Some(true));
+}
+
+/// a bmc step over the shortest depth is shortened, and the inputs that are not needed
+/// become don't care
+#[test]
+fn min_witness_is_shortest() {
+    let model = "enable_counter.aag";
+    let options = options(
+        &["-e", "bmc", "--step", "5", "--bmc-min-witness", "--witness"],
+        model,
+    );
+    let aig = load(model);
+    let Verdict::Unsafe(Some(witness)) = check(&options, &aig, &Budget::new(&options)).unwrap()
+    else {
+        panic!("expected a witness");
+    };
+    assert!(check_witness(&aig, &witness).is_ok());
+    let lines: Vec<&str> = witness.lines().collect();
+    assert_eq!(lines[3..lines.len() - 1], ["1x", "1x", "1x", "xx"]);
+}
+
+/// the sat calls of k-induction are limited by the remaining time of the budget
+#[test]
+fn kind_stops_at_time_limit() {
+    let args = ["-e", "kind", "--no-abc", "--time-limit", "1"];
+    assert_eq!(verdict(&args, "unreachable_loop.aag"), None);
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{fixture, options};
+use rIC3::{budget::Budget, certificate, check, frontend::btor::Btor, new_engine, Error, Verdict};
+use std::fs;
+
+/// the state without next function is assigned in every frame of the btor2 witness
+#[test]
+fn free_state_in_every_frame() {
+    let model = "free_state.btor2";
+    let btor = Btor::from_file(&fixture(model)).unwrap();
+    let options = options(&["-e", "bmc", "--witness"], model);
+    let Verdict::Unsafe(Some(witness)) =
+        check(&options, &btor.aig, &Budget::new(&options)).unwrap()
+    else {
+        panic!("expected a witness");
+    };
+    let witness = btor.witness_encode(&witness).unwrap();
+    let lines: Vec<&str> = witness.lines().collect();
+    for k in 1..=3 {
+        assert!(lines.contains(&format!("#{k}").as_str()));
+    }
+    assert!(lines.contains(&"1 1 noise#3"));
+    assert!(lines.contains(&"0 00 cnt#0"));
+}
+
+/// don't care values of a minimized witness are concretized and replayed
+#[test]
+fn minimized_witness_is_concrete() {
+    let model = "free_state.btor2";
+    let btor = Btor::from_file(&fixture(model)).unwrap();
+    let options = options(&["-e", "bmc", "--bmc-min-witness", "--witness"], model);
+    let Verdict::Unsafe(Some(witness)) =
+        check(&options, &btor.aig, &Budget::new(&options)).unwrap()
+    else {
+        panic!("expected a witness");
+    };
+    let witness = btor.witness_encode(&witness).unwrap();
+    assert!(!witness.contains('x'));
+}
+
+#[test]
+fn btor_verdicts() {
+    let verdict = |args: &[&str], model: &str| {
+        let btor = Btor::from_file(&fixture(model)).unwrap();
+        let options = options(args, model);
+        match check(&options, &btor.aig, &Budget::new(&options)).unwrap() {
+            Verdict::Safe(_) => Some(true),
+            Verdict::Unsafe(_) => Some(false),
+            Verdict::Unknown(_) => None,
+        }
+    };
+    assert_eq!(verdict(&["-e", "ic3"], "equal_counters.btor2"), Some(true));
+    assert_eq!(verdict(&["-e", "kind"], "equal_counters.btor2"), Some(true));
+    assert_eq!(verdict(&["-e", "bmc"], "free_state.btor2"), Some(false));
+    assert_eq!(verdict(&["-e", "ic3"], "free_state.btor2"), Some(false));
+}
+
+/// malformed or unsupported models are rejected instead of panicking
+#[test]
+fn invalid_btor2() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.btor2");
+    let invalid = |content: &str| {
+        fs::write(&path, content).unwrap();
+        matches!(
+            Btor::from_file(path.to_str().unwrap()),
+            Err(Error::InvalidInput(_))
+        )
+    };
+    assert!(invalid("1 sort bitvec 1\n2 input 1\n3 foo 1 2\n"));
+    assert!(invalid("1 sort array 1 1\n"));
+    assert!(invalid(
+        "1 sort bitvec 1\n2 sort bitvec 2\n3 input 1\n4 input 2\n5 and 1 3 4\n"
+    ));
+    assert!(invalid("1 sort bitvec 1\n2 bad 3\n"));
+    assert!(invalid("x sort bitvec 1\n"));
+}
+
+/// the aiger witness is checked before it is converted to btor2, and certificates of the
+/// bit-blasted model are checked by the built-in checker
+#[test]
+fn certify_btor2() {
+    let certify = |args: &[&str], model: &str| {
+        let btor = Btor::from_file(&fixture(model)).unwrap();
+        let args: Vec<&str> = args.iter().copied().chain(["--certify"]).collect();
+        let options = options(&args, model);
+        let mut engine = new_engine(&options, &btor.aig).unwrap();
+        let res = engine.check(&Budget::new(&options)).unwrap();
+        certificate(&mut engine, &btor.aig, &options, res, Some(&btor)).map(|_| res)
+    };
+    assert!(!certify(&["-e", "bmc"], "free_state.btor2").unwrap());
+    assert!(certify(&["-e", "ic3"], "equal_counters.btor2").unwrap());
+    assert!(matches!(
+        certify(&["-e", "bmc", "--certify-docker"], "free_state.btor2"),
+        Err(Error::Unsupported(_))
+    ));
+}
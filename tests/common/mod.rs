@@ -0,0 +1,52 @@
+#![allow(dead_code)]
+
+use aig::Aig;
+use clap::Parser;
+use rIC3::{
+    budget::Budget, certify::check_certificate, check, check_witness, options::Options, Verdict,
+};
+
+pub fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+pub fn options(args: &[&str], model: &str) -> Options {
+    let model = fixture(model);
+    let args = ["rIC3", "-v", "0"]
+        .into_iter()
+        .chain(args.iter().copied())
+        .chain([model.as_str()]);
+    Options::parse_from(args)
+}
+
+pub fn load(model: &str) -> Aig {
+    Aig::from_file(&fixture(model))
+}
+
+/// the verdict of the model checked with the arguments, none if unknown
+pub fn verdict(args: &[&str], model: &str) -> Option<bool> {
+    let options = options(args, model);
+    match check(&options, &load(model), &Budget::new(&options)).unwrap() {
+        Verdict::Safe(_) => Some(true),
+        Verdict::Unsafe(_) => Some(false),
+        Verdict::Unknown(_) => None,
+    }
+}
+
+/// the verdict with --certify, the certificate or witness is checked by the built-in checker
+pub fn certified(args: &[&str], model: &str) -> Option<bool> {
+    let args: Vec<&str> = args.iter().copied().chain(["--certify"]).collect();
+    let options = options(&args, model);
+    let aig = load(model);
+    match check(&options, &aig, &Budget::new(&options)).unwrap() {
+        Verdict::Safe(certificate) => {
+            assert!(check_certificate(&aig, &certificate.unwrap()).is_ok());
+            Some(true)
+        }
+        Verdict::Unsafe(witness) => {
+            assert!(check_witness(&aig, &witness.unwrap()).is_ok());
+            Some(false)
+        }
+        Verdict::Unknown(_) => None,
+    }
+}
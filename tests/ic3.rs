@@ -0,0 +1,46 @@
+mod common;
+
+use common::{certified, load, options};
+use rIC3::{budget::Budget, check_witness, ic3::IC3, transys, Engine};
+use std::{thread, time::Duration};
+
+#[test]
+fn ic3_certifies_results() {
+    assert_eq!(certified(&["-e", "ic3"], "counter_unsafe.aag"), Some(false));
+    assert_eq!(certified(&["-e", "ic3"], "mod3_safe.aag"), Some(true));
+}
+
+/// the stuck latch is constant, the certificate restores it from the merged latchs
+#[test]
+fn const_latch_is_opt_in() {
+    let model = "unreachable_loop.aag";
+    assert_eq!(certified(&["-e", "ic3", "--no-abc"], model), Some(true));
+    assert_eq!(
+        certified(&["-e", "ic3", "--no-abc", "--const-latch"], model),
+        Some(true)
+    );
+}
+
+/// the obligations left by an exhausted budget are kept, the resumed check still finds
+/// a valid counterexample
+#[test]
+fn ic3_resumes_after_cancel() {
+    let model = "counter_unsafe.aag";
+    let options = options(&["-e", "ic3"], model);
+    let aig = load(model);
+    let mut ic3 = IC3::new(options.clone(), transys(&options, &aig).unwrap(), vec![]);
+    let res = (0u64..).find_map(|delay| {
+        let budget = Budget::new(&options);
+        let cancel = budget.clone();
+        let timer = thread::spawn(move || {
+            thread::sleep(Duration::from_micros(delay * 10));
+            cancel.cancel();
+        });
+        let res = ic3.check(&budget);
+        timer.join().unwrap();
+        res
+    });
+    assert_eq!(res, Some(false));
+    let witness = ic3.witness(&aig).unwrap();
+    assert!(check_witness(&aig, &witness).is_ok());
+}
@@ -0,0 +1,15 @@
+mod common;
+
+use common::{certified, verdict};
+
+#[test]
+fn imc_finds_counterexample() {
+    assert_eq!(verdict(&["-e", "imc"], "counter_unsafe.aag"), Some(false));
+    assert_eq!(certified(&["-e", "imc"], "counter_unsafe.aag"), Some(false));
+}
+
+#[test]
+fn imc_proves_safe() {
+    assert_eq!(verdict(&["-e", "imc"], "mod3_safe.aag"), Some(true));
+    assert_eq!(certified(&["-e", "imc"], "mod3_safe.aag"), Some(true));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{fixture, load, options};
+use rIC3::{
+    budget::Budget, export_invariant, invariant::read_lemmas, new_engine, pre_lemmas, transys,
+    Error,
+};
+use std::{path::Path, process::Command};
+use tempfile::TempDir;
+
+/// the clauses of the invariant exported after a safe result
+fn export(args: &[&str], model: &str) -> Result<usize, Error> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("invariant.cnf");
+    let args: Vec<&str> = args
+        .iter()
+        .copied()
+        .chain(["--invariant", path.to_str().unwrap()])
+        .collect();
+    let options = options(&args, model);
+    let aig = load(model);
+    let mut engine = new_engine(&options, &aig)?;
+    assert_eq!(engine.check(&Budget::new(&options)), Some(true));
+    export_invariant(&mut engine, &aig, &options)?;
+    Ok(read_lemmas(&aig, &path)?.len())
+}
+
+#[test]
+fn ic3_exports_invariant() {
+    assert!(export(&["-e", "ic3"], "mod3_safe.aag").unwrap() > 0);
+}
+
+#[test]
+fn thread_portfolio_forwards_invariant() {
+    let args = ["-e", "portfolio", "--portfolio-thread", "--jobs", "4"];
+    assert!(export(&args, "mod3_safe.aag").unwrap() > 0);
+}
+
+#[test]
+fn invariant_of_kind_is_unsupported() {
+    assert!(matches!(
+        export(&["-e", "kind"], "mod3_safe.aag"),
+        Err(Error::Unsupported(_))
+    ));
+}
+
+/// a deeper correspondence is reduced to depth 1 to keep the exported invariant inductive
+#[test]
+fn scorr_invariant_uses_depth_one() {
+    let args = ["-e", "ic3", "--scorr", "--scorr-depth", "3"];
+    assert!(export(&args, "mod3_safe.aag").unwrap() > 0);
+}
+
+/// an exported invariant is read back as inductive lemmas seeding ic3 of the same model
+#[test]
+fn exported_invariant_is_imported() {
+    let model = "mod3_safe.aag";
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("invariant.cnf");
+    let path = path.to_str().unwrap();
+    let export_options = options(&["-e", "ic3", "--invariant", path], model);
+    let aig = load(model);
+    let mut engine = new_engine(&export_options, &aig).unwrap();
+    assert_eq!(engine.check(&Budget::new(&export_options)), Some(true));
+    export_invariant(&mut engine, &aig, &export_options).unwrap();
+    assert!(!read_lemmas(&aig, Path::new(path)).unwrap().is_empty());
+    let options = options(&["-e", "ic3", "--lemmas", path], model);
+    let ts = transys(&options, &aig).unwrap();
+    assert!(!pre_lemmas(&options, &aig, &ts).unwrap().is_empty());
+    assert_eq!(
+        new_engine(&options, &aig)
+            .unwrap()
+            .check(&Budget::new(&options)),
+        Some(true)
+    );
+}
+
+/// the process portfolio spawns the rIC3 binary, only its ic3 and abs engines are given
+/// --invariant while bmc engines run without it
+#[test]
+fn portfolio_forwards_invariant() {
+    let model = "mod3_safe.aag";
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("invariant.cnf");
+    let status = Command::new(env!("CARGO_BIN_EXE_rIC3"))
+        .args(["-v", "0", "-e", "portfolio", "--invariant"])
+        .arg(&path)
+        .arg(fixture(model))
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(20));
+    assert!(!read_lemmas(&load(model), &path).unwrap().is_empty());
+}
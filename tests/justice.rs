@@ -0,0 +1,19 @@
+mod common;
+
+use common::{load, options, verdict};
+use rIC3::{budget::Budget, check, Error};
+
+#[test]
+fn justice_verdicts() {
+    assert_eq!(verdict(&["-e", "ic3"], "toggle_justice.aag"), Some(false));
+    assert_eq!(verdict(&["-e", "ic3"], "stuck_justice.aag"), Some(true));
+}
+
+/// the certificate of the liveness-to-safety model does not certify the justice property
+#[test]
+fn justice_certificate_is_unsupported() {
+    let model = "stuck_justice.aag";
+    let options = options(&["-e", "ic3", "--certify"], model);
+    let res = check(&options, &load(model), &Budget::new(&options));
+    assert!(matches!(res, Err(Error::Unsupported(_))));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::{load, options};
+use rIC3::{
+    budget::Budget, check_witness, frontend::aig::liveness_to_safety, multiprop::MultiProp,
+};
+use std::fs;
+
+/// the witness of each property is encoded on the original aig, a justice property gets
+/// a lasso witness instead of the bad of liveness-to-safety
+#[test]
+fn justice_witness_on_origin() {
+    let model = "bad_and_justice.aag";
+    let dir = tempfile::tempdir().unwrap();
+    let certificate = dir.path().join("witness");
+    let mut options = options(&["-e", "bmc", "--multi-prop"], model);
+    options.certificate = Some(certificate.clone());
+    let origin = load(model);
+    let aig = liveness_to_safety(&origin);
+    let res = MultiProp::new(options.clone(), &aig, &origin)
+        .check(None, &Budget::new(&options))
+        .unwrap();
+    assert_eq!(res, vec![Some(false), Some(false)]);
+    let bad = fs::read_to_string(format!("{}.0", certificate.display())).unwrap();
+    assert!(bad.starts_with("1\nb0\n"));
+    let mut safety = origin.clone();
+    safety.justice.clear();
+    assert!(check_witness(&safety, &bad).is_ok());
+    let justice = fs::read_to_string(format!("{}.1", certificate.display())).unwrap();
+    assert!(justice.starts_with("1\nj0\n"));
+}
+
+#[test]
+fn per_property_verdicts() {
+    let model = "two_properties.aag";
+    let options = options(&["-e", "ic3", "--multi-prop"], model);
+    let aig = load(model);
+    let res = MultiProp::new(options.clone(), &aig, &aig)
+        .check(None, &Budget::new(&options))
+        .unwrap();
+    assert_eq!(res, vec![Some(false), Some(true)]);
+}
+
+/// the default portfolio engine falls back to ic3, each property is certified against
+/// the model with that property only
+#[test]
+fn certified_portfolio_verdicts() {
+    let model = "two_properties.aag";
+    let options = options(&["--multi-prop", "--certify"], model);
+    let aig = load(model);
+    let res = MultiProp::new(options.clone(), &aig, &aig)
+        .check(None, &Budget::new(&options))
+        .unwrap();
+    assert_eq!(res, vec![Some(false), Some(true)]);
+}
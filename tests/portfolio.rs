@@ -0,0 +1,43 @@
+use rIC3::{portfolio::PortfolioConfig, Error};
+use std::fs;
+
+fn config(name: &str, content: &str) -> Result<PortfolioConfig, Error> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(name);
+    fs::write(&path, content).unwrap();
+    PortfolioConfig::from_file(&path)
+}
+
+#[test]
+fn config_formats() {
+    let toml = config("config.toml", "[[engine]]\nargs = \"-e ic3\"\nweight = 3\n").unwrap();
+    assert_eq!(toml.engines.len(), 1);
+    assert_eq!(toml.engines[0].weight, 3);
+    let json = config("config.json", r#"{"engine": [{"args": "-e bmc"}]}"#).unwrap();
+    assert_eq!(json.engines[0].args, "-e bmc");
+    assert_eq!(json.engines[0].weight, 1);
+}
+
+#[test]
+fn empty_toml_config() {
+    assert!(matches!(
+        config("config.toml", "engine = []\n"),
+        Err(Error::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn empty_json_config() {
+    assert!(matches!(
+        config("config.json", r#"{"engine": []}"#),
+        Err(Error::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn malformed_config() {
+    assert!(matches!(
+        config("config.toml", "[[engine]]\nweight = 3\n"),
+        Err(Error::InvalidInput(_))
+    ));
+}
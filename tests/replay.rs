@@ -0,0 +1,24 @@
+mod common;
+
+use common::options;
+use rIC3::{options::Engine, portfolio::replay::Replay};
+
+/// the replayed options run the recorded engine and seed, the model and the requested
+/// outputs are kept from the current run
+#[test]
+fn replay_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("replay.json");
+    let recorded = options(&["-e", "portfolio"], "mod3_safe.aag");
+    let args = ["-e", "bmc", "--step", "10", "--rseed", "7"];
+    Replay::new(args.map(String::from).to_vec(), &recorded)
+        .save(&path)
+        .unwrap();
+    let current = options(&["--witness"], "counter_unsafe.aag");
+    let replayed = Replay::load(&path).unwrap().options(&current).unwrap();
+    assert!(matches!(replayed.engine, Engine::BMC));
+    assert_eq!(replayed.step, 10);
+    assert_eq!(replayed.rseed, 7);
+    assert!(replayed.witness);
+    assert_eq!(replayed.model, current.model);
+}
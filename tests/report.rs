@@ -0,0 +1,34 @@
+mod common;
+
+use common::{load, options};
+use rIC3::{budget::Budget, new_engine, report::Report};
+
+fn report(args: &[&str], model: &str) -> serde_json::Value {
+    let options = options(args, model);
+    let mut engine = new_engine(&options, &load(model)).unwrap();
+    let mut report = Report::new(&options);
+    report.verdict = engine.check(&Budget::new(&options));
+    engine.report(&mut report);
+    report.to_json()
+}
+
+#[test]
+fn bmc_and_kind_statistics() {
+    let bmc = report(&["-e", "bmc"], "counter_unsafe.aag");
+    assert_eq!(bmc["verdict"], "unsafe");
+    assert!(bmc["statistic"]["bmc"]["num_solve"].as_u64().unwrap() > 0);
+    assert!(bmc["statistic"]["bmc"]["solve_time"].is_f64());
+    let kind = report(&["-e", "kind"], "counter_unsafe.aag");
+    assert_eq!(kind["verdict"], "unsafe");
+    assert!(kind["statistic"]["kind"]["num_solve"].as_u64().unwrap() > 0);
+}
+
+/// averages and success rates are json numbers, not debug strings
+#[test]
+fn ic3_statistics_are_numbers() {
+    let ic3 = report(&["-e", "ic3"], "mod3_safe.aag");
+    let statistic = &ic3["statistic"]["ic3"];
+    assert!(statistic["time"].is_f64());
+    assert!(statistic["mic_drop"]["success"].is_u64());
+    assert!(statistic["avg_mic_cube_len"]["avg"].is_f64());
+}
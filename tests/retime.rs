@@ -0,0 +1,17 @@
+mod common;
+
+use common::{load, options};
+use rIC3::{budget::Budget, check, check_witness, Verdict};
+
+/// the witness of the retimed model is translated back and replayed on the original aig
+#[test]
+fn retimed_witness_replays_on_original() {
+    let model = "counter_unsafe.aag";
+    let options = options(&["-e", "bmc", "--retime", "--witness"], model);
+    let aig = load(model);
+    let Verdict::Unsafe(Some(witness)) = check(&options, &aig, &Budget::new(&options)).unwrap()
+    else {
+        panic!("expected a witness");
+    };
+    assert!(check_witness(&aig, &witness).is_ok());
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use common::{fixture, load, options};
+use rIC3::{budget::Budget, check, frontend::aig::miter, Verdict};
+
+fn sec(model: &str, reference: &str) -> Option<bool> {
+    let path = fixture(reference);
+    let options = options(&["-e", "ic3", "--sec", &path], model);
+    let aig = miter(&load(model), &load(reference));
+    match check(&options, &aig, &Budget::new(&options)).unwrap() {
+        Verdict::Safe(_) => Some(true),
+        Verdict::Unsafe(_) => Some(false),
+        Verdict::Unknown(_) => None,
+    }
+}
+
+#[test]
+fn equivalent_models() {
+    assert_eq!(sec("mod3_safe.aag", "mod3_safe.aag"), Some(true));
+    assert_eq!(sec("counter_unsafe.aag", "counter_unsafe.aag"), Some(true));
+}
+
+/// the counter reaches the state with both bits set, the modulo 3 counter does not
+#[test]
+fn different_models() {
+    assert_eq!(sec("counter_unsafe.aag", "mod3_safe.aag"), Some(false));
+}
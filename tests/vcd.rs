@@ -0,0 +1,28 @@
+mod common;
+
+use common::{load, options};
+use rIC3::{budget::Budget, certificate, new_engine};
+use std::fs;
+
+/// outputs and bads are named by the symbol table, the scalar req is renamed as the bus
+/// req holds the bit req[0] in the same scope
+#[test]
+fn vcd_symbols() {
+    let model = "named_signals.aag";
+    let dir = tempfile::tempdir().unwrap();
+    let vcd = dir.path().join("trace.vcd");
+    let mut options = options(&["-e", "bmc"], model);
+    options.vcd = Some(vcd.clone());
+    let aig = load(model);
+    let mut engine = new_engine(&options, &aig).unwrap();
+    assert_eq!(engine.check(&Budget::new(&options)), Some(false));
+    certificate(&mut engine, &aig, &options, false, None).unwrap();
+    let vcd = fs::read_to_string(vcd).unwrap();
+    let vars: Vec<&str> = vcd
+        .lines()
+        .filter_map(|l| l.strip_prefix("$var wire "))
+        .filter_map(|l| l.strip_suffix(" $end"))
+        .filter_map(|l| l.split_whitespace().nth(2))
+        .collect();
+    assert_eq!(vars, ["req_1", "req", "state", "hit", "fail"]);
+}
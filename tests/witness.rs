@@ -0,0 +1,35 @@
+mod common;
+
+use common::{load, options};
+use rIC3::{budget::Budget, check, check_witness, frontend::aig::outputs_to_bads, Verdict};
+
+/// the witness of a model whose property is an output checks against the same mapping
+/// of outputs to bads
+#[test]
+fn output_property_witness() {
+    let model = "counter_output.aag";
+    let mut aig = load(model);
+    assert!(check_witness(&aig, "1\nb0\n00\n\n\n\n\n.\n").is_err());
+    assert!(outputs_to_bads(&mut aig));
+    let options = options(&["-e", "bmc", "--witness"], model);
+    let Verdict::Unsafe(Some(witness)) = check(&options, &aig, &Budget::new(&options)).unwrap()
+    else {
+        panic!("expected a witness");
+    };
+    assert!(check_witness(&aig, &witness).is_ok());
+    let mut unmapped = load(model);
+    unmapped.outputs.clear();
+    assert!(check_witness(&unmapped, &witness).is_err());
+}
+
+/// --witness does not ask for a certificate, a safe result of an engine without one
+/// is not rejected
+#[test]
+fn witness_of_safe_result() {
+    let model = "equal_toggles.aag";
+    let options = options(&["-e", "kind", "--no-abc", "--scorr", "--witness"], model);
+    assert!(matches!(
+        check(&options, &load(model), &Budget::new(&options)).unwrap(),
+        Verdict::Safe(None)
+    ));
+}